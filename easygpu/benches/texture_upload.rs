@@ -0,0 +1,90 @@
+//! Compares uploading texels with `Queue::write_texture`, which `Texture::fill`
+//! and `Texture::transfer` use, against copying them from a temporary buffer
+//! created for each upload.
+//!
+//! Requires a graphics adapter. The benchmarks are skipped when none is found.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use easygpu::prelude::*;
+use figures::{Point, Size, SizedRect};
+
+const FULL_FRAME: Size<u32, ScreenSpace> = Size::new(1920, 1080);
+const SUB_RECT: Size<u32, ScreenSpace> = Size::new(64, 64);
+
+fn texture_upload(c: &mut Criterion) {
+    let renderer = match futures::executor::block_on(Renderer::headless(
+        &AdapterPolicy::default(),
+        1,
+        &DeviceOptions::default(),
+    )) {
+        Ok(renderer) => renderer,
+        Err(err) => {
+            eprintln!("skipping texture upload benchmarks: {}", err);
+            return;
+        }
+    };
+    let device = &renderer.device;
+    let texture = device.create_texture(
+        FULL_FRAME,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        1,
+        Some("benchmark texture"),
+    );
+
+    let mut group = c.benchmark_group("texture_upload");
+    for (name, size) in [("full_frame", FULL_FRAME), ("sub_rect", SUB_RECT)] {
+        let texels = vec![Rgba8::new(0x33, 0x66, 0x99, 0xff); size.area().get() as usize];
+        let rect = SizedRect::new(Point::default(), size.cast::<i32>());
+        group.throughput(Throughput::Bytes(u64::from(size.area().get()) * 4));
+
+        group.bench_with_input(
+            BenchmarkId::new("write_texture", name),
+            &texels,
+            |b, texels| {
+                b.iter(|| {
+                    Texture::transfer(&texture, texels, rect, device).unwrap();
+                    device.queue.submit(None);
+                    device.wgpu.poll(wgpu::Maintain::Wait);
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("temporary_buffer", name),
+            &texels,
+            |b, texels| {
+                b.iter(|| {
+                    let buffer = device.create_buffer_from_slice(
+                        texels,
+                        wgpu::BufferUsages::COPY_SRC,
+                        Some("benchmark upload"),
+                    );
+                    let mut encoder = device.create_command_encoder();
+                    encoder.copy_buffer_to_texture(
+                        wgpu::ImageCopyBuffer {
+                            buffer: &buffer,
+                            layout: wgpu::ImageDataLayout {
+                                offset: 0,
+                                bytes_per_row: Some(size.width * 4),
+                                rows_per_image: Some(size.height),
+                            },
+                        },
+                        texture.wgpu.as_image_copy(),
+                        wgpu::Extent3d {
+                            width: size.width,
+                            height: size.height,
+                            depth_or_array_layers: 1,
+                        },
+                    );
+                    device.queue.submit(Some(encoder.finish()));
+                    device.wgpu.poll(wgpu::Maintain::Wait);
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, texture_upload);
+criterion_main!(benches);
@@ -0,0 +1,233 @@
+use crate::error::Error;
+
+/// Controls which [`wgpu::Adapter`] is chosen when easygpu selects one.
+#[derive(Debug, Clone)]
+pub struct AdapterPolicy {
+    /// The backends adapters may be chosen from.
+    pub backends: wgpu::Backends,
+    /// Which kind of adapter is preferred when several are acceptable.
+    pub power_preference: wgpu::PowerPreference,
+    /// Allows software and fallback adapters (e.g. llvmpipe, lavapipe or WARP)
+    /// to be selected. They are still only chosen if no hardware adapter is
+    /// acceptable.
+    pub allow_fallback: bool,
+}
+
+impl Default for AdapterPolicy {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            allow_fallback: false,
+        }
+    }
+}
+
+impl AdapterPolicy {
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_fallback(mut self, allow_fallback: bool) -> Self {
+        self.allow_fallback = allow_fallback;
+        self
+    }
+
+    /// Creates a new [`wgpu::Instance`] restricted to this policy's backends.
+    pub fn instance(&self) -> wgpu::Instance {
+        wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..wgpu::InstanceDescriptor::default()
+        })
+    }
+
+    /// Selects the best adapter from `instance` that satisfies this policy. If
+    /// `compatible_surface` is provided, only adapters that can present to it
+    /// are considered.
+    ///
+    /// If no adapter is acceptable, [`Error::NoAdaptersFound`] is returned with
+    /// the information of every adapter that was rejected.
+    pub fn select(
+        &self,
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface>,
+    ) -> Result<wgpu::Adapter, Error> {
+        self.best(instance.enumerate_adapters(self.backends).map(|adapter| {
+            let presentable = compatible_surface
+                .map(|surface| adapter.is_surface_supported(surface))
+                .unwrap_or(true);
+            (adapter.get_info(), presentable, adapter)
+        }))
+    }
+
+    /// Returns the best of `candidates`, which are each an adapter's
+    /// information, whether it can present to the compatible surface, and the
+    /// adapter. Of equally ranked adapters, the first is chosen.
+    fn best<A>(
+        &self,
+        candidates: impl IntoIterator<Item = (wgpu::AdapterInfo, bool, A)>,
+    ) -> Result<A, Error> {
+        let mut rejected = Vec::new();
+        let mut best: Option<(u8, A)> = None;
+
+        for (info, presentable, adapter) in candidates {
+            let rank = match self.rank(&info, presentable) {
+                Some(rank) => rank,
+                None => {
+                    rejected.push(info);
+                    continue;
+                }
+            };
+            if !matches!(&best, Some((best, _)) if *best >= rank) {
+                best = Some((rank, adapter));
+            }
+        }
+
+        best.map(|(_, adapter)| adapter)
+            .ok_or(Error::NoAdaptersFound { rejected })
+    }
+
+    /// Returns the rank of the adapter described by `info`, where higher
+    /// ranks are preferred, or `None` if this policy doesn't accept it.
+    fn rank(&self, info: &wgpu::AdapterInfo, presentable: bool) -> Option<u8> {
+        let in_backends = wgpu::Backends::from_bits(1 << info.backend as u32)
+            .is_some_and(|backend| self.backends.contains(backend));
+        let is_fallback = info.device_type == wgpu::DeviceType::Cpu;
+        if !in_backends || (is_fallback && !self.allow_fallback) || !presentable {
+            return None;
+        }
+
+        Some(match (self.power_preference, info.device_type) {
+            (_, wgpu::DeviceType::Cpu) => 0,
+            (_, wgpu::DeviceType::Other) => 1,
+            (_, wgpu::DeviceType::VirtualGpu) => 2,
+            (wgpu::PowerPreference::LowPower, wgpu::DeviceType::DiscreteGpu) => 3,
+            (wgpu::PowerPreference::LowPower, wgpu::DeviceType::IntegratedGpu) => 4,
+            (_, wgpu::DeviceType::IntegratedGpu) => 3,
+            (_, wgpu::DeviceType::DiscreteGpu) => 4,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{AdapterInfo, Backend, Backends, DeviceType, PowerPreference};
+
+    use super::AdapterPolicy;
+    use crate::error::Error;
+
+    fn info(name: &str, device_type: DeviceType, backend: Backend) -> AdapterInfo {
+        AdapterInfo {
+            name: name.to_string(),
+            vendor: 0,
+            device: 0,
+            device_type,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend,
+        }
+    }
+
+    /// Selects the name of the best adapter of `adapters`, which are all
+    /// presentable.
+    fn select(policy: &AdapterPolicy, adapters: &[AdapterInfo]) -> Result<String, Error> {
+        policy.best(
+            adapters
+                .iter()
+                .map(|info| (info.clone(), true, info.name.clone())),
+        )
+    }
+
+    fn rejected(result: Result<String, Error>) -> Vec<String> {
+        match result {
+            Err(Error::NoAdaptersFound { rejected }) => {
+                rejected.into_iter().map(|info| info.name).collect()
+            }
+            other => panic!("expected no adapters to be found, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn power_preference_selects_the_device_type() {
+        let adapters = [
+            info("virtual", DeviceType::VirtualGpu, Backend::Vulkan),
+            info("integrated", DeviceType::IntegratedGpu, Backend::Vulkan),
+            info("discrete", DeviceType::DiscreteGpu, Backend::Vulkan),
+        ];
+        let policy = AdapterPolicy::default();
+
+        assert_eq!(
+            select(
+                &policy
+                    .clone()
+                    .with_power_preference(PowerPreference::HighPerformance),
+                &adapters
+            )
+            .unwrap(),
+            "discrete"
+        );
+        assert_eq!(
+            select(
+                &policy.with_power_preference(PowerPreference::LowPower),
+                &adapters
+            )
+            .unwrap(),
+            "integrated"
+        );
+    }
+
+    #[test]
+    fn adapters_outside_of_the_backends_are_rejected() {
+        let adapters = [
+            info("vulkan", DeviceType::IntegratedGpu, Backend::Vulkan),
+            info("gl", DeviceType::DiscreteGpu, Backend::Gl),
+        ];
+        let policy =
+            AdapterPolicy::default().with_power_preference(PowerPreference::HighPerformance);
+
+        assert_eq!(select(&policy, &adapters).unwrap(), "gl");
+        assert_eq!(
+            select(&policy.clone().with_backends(Backends::VULKAN), &adapters).unwrap(),
+            "vulkan"
+        );
+        assert_eq!(
+            rejected(select(&policy.with_backends(Backends::METAL), &adapters)),
+            ["vulkan", "gl"]
+        );
+    }
+
+    #[test]
+    fn fallback_adapters_are_only_chosen_when_allowed_and_nothing_else_is() {
+        let adapters = [
+            info("cpu", DeviceType::Cpu, Backend::Vulkan),
+            info("gpu", DeviceType::IntegratedGpu, Backend::Vulkan),
+        ];
+        let policy = AdapterPolicy::default();
+
+        assert_eq!(rejected(select(&policy, &adapters[..1])), ["cpu"]);
+        assert_eq!(select(&policy, &adapters).unwrap(), "gpu");
+        let policy = policy.with_fallback(true);
+        assert_eq!(select(&policy, &adapters[..1]).unwrap(), "cpu");
+        assert_eq!(select(&policy, &adapters).unwrap(), "gpu");
+    }
+
+    #[test]
+    fn adapters_that_cant_present_are_rejected() {
+        let discrete = info("discrete", DeviceType::DiscreteGpu, Backend::Vulkan);
+        let integrated = info("integrated", DeviceType::IntegratedGpu, Backend::Vulkan);
+        let policy =
+            AdapterPolicy::default().with_power_preference(PowerPreference::HighPerformance);
+
+        let best = policy.best(vec![
+            (discrete, false, "discrete"),
+            (integrated, true, "integrated"),
+        ]);
+        assert_eq!(best.unwrap(), "integrated");
+    }
+}
@@ -1,4 +1,9 @@
-use wgpu::ShaderStages;
+use std::num::{NonZeroU32, NonZeroU64};
+
+use wgpu::{ShaderStages, StorageTextureAccess, TextureFormat, TextureViewDimension};
+
+use crate::sampler::Sampler;
+use crate::texture::Texture;
 
 /// A group of bindings.
 #[derive(Debug)]
@@ -33,43 +38,262 @@ impl BindingGroupLayout {
 
 /// A trait representing a resource that can be bound.
 pub trait Bind {
-    fn binding(&self, index: u32) -> wgpu::BindGroupEntry;
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_>;
 }
 
 /// A binding type.
 #[derive(Debug)]
 pub enum BindingType {
     UniformBuffer,
+    /// A uniform buffer bound at an offset passed to
+    /// [`RenderPassExt::set_binding`](crate::renderer::RenderPassExt::set_binding),
+    /// such as a [`DynamicUniformBuffer`](crate::buffers::DynamicUniformBuffer).
     UniformBufferDynamic,
+    /// A storage buffer, declared as `var<storage, read>` if `read_only` is
+    /// true, or `var<storage, read_write>` otherwise.
+    StorageBuffer {
+        read_only: bool,
+    },
     Sampler,
-    SampledTexture { multisampled: bool },
+    /// A sampler that compares depth values, such as one created with
+    /// [`SamplerDescriptor::with_compare`](crate::sampler::SamplerDescriptor::with_compare).
+    ComparisonSampler,
+    /// A sampler that only uses [`wgpu::FilterMode::Nearest`], which is
+    /// required to sample unfilterable textures.
+    NonFilteringSampler,
+    SampledTexture {
+        multisampled: bool,
+    },
+    /// A 2D texture array, sampled as `texture_2d_array`.
+    SampledTextureArray,
+    /// A cube map, sampled as `texture_cube`.
+    SampledTextureCube,
+    /// An array of cube maps, sampled as `texture_cube_array`.
+    SampledTextureCubeArray,
+    /// A 3D texture, sampled as `texture_3d`.
+    SampledTexture3d,
+    /// A 2D depth texture, sampled as `texture_depth_2d`.
+    DepthTexture {
+        multisampled: bool,
+    },
+    /// A 2D texture of unsigned integers, sampled as `texture_2d<u32>`.
+    UintTexture {
+        multisampled: bool,
+    },
+    /// A 2D texture of signed integers, sampled as `texture_2d<i32>`.
+    SintTexture {
+        multisampled: bool,
+    },
+    /// A 2D float texture that can't be filtered, such as an `R32Float`
+    /// texture.
+    UnfilterableTexture {
+        multisampled: bool,
+    },
+    /// A texture of any sample type and view dimension.
+    Texture {
+        sample_type: wgpu::TextureSampleType,
+        view_dimension: TextureViewDimension,
+        multisampled: bool,
+    },
+    /// A texture that shaders read or write texels of directly, declared as
+    /// `texture_storage_2d<format, access>` for a 2D view dimension.
+    StorageTexture {
+        access: StorageTextureAccess,
+        format: TextureFormat,
+        view_dimension: TextureViewDimension,
+    },
 }
 
 impl BindingType {
     pub fn to_wgpu(&self) -> wgpu::BindingType {
+        self.to_wgpu_with_min_binding_size(None)
+    }
+
+    /// Returns the binding type, requiring bound buffers to contain at least
+    /// `min_binding_size` bytes. The size is ignored by other binding types.
+    pub fn to_wgpu_with_min_binding_size(
+        &self,
+        min_binding_size: Option<NonZeroU64>,
+    ) -> wgpu::BindingType {
         match self {
             BindingType::UniformBuffer => wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
                 has_dynamic_offset: false,
-                min_binding_size: None,
+                min_binding_size,
             },
             BindingType::UniformBufferDynamic => wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size,
+            },
+            BindingType::StorageBuffer { read_only } => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage {
+                    read_only: *read_only,
+                },
                 has_dynamic_offset: false,
-                min_binding_size: None,
+                min_binding_size,
             },
             BindingType::SampledTexture { multisampled } => wgpu::BindingType::Texture {
                 sample_type: wgpu::TextureSampleType::Float { filterable: true },
                 multisampled: *multisampled,
-                view_dimension: wgpu::TextureViewDimension::D2,
+                view_dimension: TextureViewDimension::D2,
+            },
+            BindingType::SampledTextureArray => sampled_texture(TextureViewDimension::D2Array),
+            BindingType::SampledTextureCube => sampled_texture(TextureViewDimension::Cube),
+            BindingType::SampledTextureCubeArray => {
+                sampled_texture(TextureViewDimension::CubeArray)
+            }
+            BindingType::SampledTexture3d => sampled_texture(TextureViewDimension::D3),
+            BindingType::DepthTexture { multisampled } => {
+                texture_2d(wgpu::TextureSampleType::Depth, *multisampled)
+            }
+            BindingType::UintTexture { multisampled } => {
+                texture_2d(wgpu::TextureSampleType::Uint, *multisampled)
+            }
+            BindingType::SintTexture { multisampled } => {
+                texture_2d(wgpu::TextureSampleType::Sint, *multisampled)
+            }
+            BindingType::UnfilterableTexture { multisampled } => texture_2d(
+                wgpu::TextureSampleType::Float { filterable: false },
+                *multisampled,
+            ),
+            BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled,
+            } => wgpu::BindingType::Texture {
+                sample_type: *sample_type,
+                multisampled: *multisampled,
+                view_dimension: *view_dimension,
+            },
+            BindingType::StorageTexture {
+                access,
+                format,
+                view_dimension,
+            } => wgpu::BindingType::StorageTexture {
+                access: *access,
+                format: *format,
+                view_dimension: *view_dimension,
             },
             BindingType::Sampler => wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            BindingType::ComparisonSampler => {
+                wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)
+            }
+            BindingType::NonFilteringSampler => {
+                wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering)
+            }
         }
     }
 }
 
+fn sampled_texture(view_dimension: TextureViewDimension) -> wgpu::BindingType {
+    wgpu::BindingType::Texture {
+        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+        multisampled: false,
+        view_dimension,
+    }
+}
+
+fn texture_2d(sample_type: wgpu::TextureSampleType, multisampled: bool) -> wgpu::BindingType {
+    wgpu::BindingType::Texture {
+        sample_type,
+        multisampled,
+        view_dimension: TextureViewDimension::D2,
+    }
+}
+
 #[derive(Debug)]
 pub struct Binding {
     pub binding: BindingType,
     pub stage: ShaderStages,
+    /// The number of elements in a binding array, or `None` for a single
+    /// resource. Binding arrays require
+    /// [`wgpu::Features::TEXTURE_BINDING_ARRAY`] or
+    /// [`wgpu::Features::BUFFER_BINDING_ARRAY`].
+    pub count: Option<NonZeroU32>,
+    /// The minimum size in bytes of a bound buffer, which lets wgpu validate
+    /// the size when the binding group is created instead of at each draw.
+    pub min_binding_size: Option<NonZeroU64>,
+}
+
+impl Binding {
+    pub fn new(binding: BindingType, stage: ShaderStages) -> Self {
+        Self {
+            binding,
+            stage,
+            count: None,
+            min_binding_size: None,
+        }
+    }
+
+    /// Makes this a binding array of `count` resources, bound with a
+    /// [`TextureArrayBinding`] or [`SamplerArrayBinding`].
+    pub fn with_count(mut self, count: NonZeroU32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn with_min_binding_size(mut self, size: NonZeroU64) -> Self {
+        self.min_binding_size = Some(size);
+        self
+    }
+
+    pub fn to_wgpu(&self, index: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: index,
+            visibility: self.stage,
+            ty: self
+                .binding
+                .to_wgpu_with_min_binding_size(self.min_binding_size),
+            count: self.count,
+        }
+    }
+}
+
+/// Binds an array of textures to a [`Binding`] created with
+/// [`Binding::with_count`].
+#[derive(Debug)]
+pub struct TextureArrayBinding<'a> {
+    views: Vec<&'a wgpu::TextureView>,
+}
+
+impl<'a> TextureArrayBinding<'a> {
+    pub fn new(textures: &[&'a Texture]) -> Self {
+        Self {
+            views: textures.iter().map(|texture| &texture.view).collect(),
+        }
+    }
+}
+
+impl<'a> Bind for TextureArrayBinding<'a> {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: index,
+            resource: wgpu::BindingResource::TextureViewArray(&self.views),
+        }
+    }
+}
+
+/// Binds an array of samplers to a [`Binding`] created with
+/// [`Binding::with_count`].
+#[derive(Debug)]
+pub struct SamplerArrayBinding<'a> {
+    samplers: Vec<&'a wgpu::Sampler>,
+}
+
+impl<'a> SamplerArrayBinding<'a> {
+    pub fn new(samplers: &[&'a Sampler]) -> Self {
+        Self {
+            samplers: samplers.iter().map(|sampler| &*sampler.wgpu).collect(),
+        }
+    }
+}
+
+impl<'a> Bind for SamplerArrayBinding<'a> {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: index,
+            resource: wgpu::BindingResource::SamplerArray(&self.samplers),
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::binding::Bind;
 use crate::texture::Texture;
 
 /// Z-Depth buffer.
@@ -8,4 +9,16 @@ pub struct DepthBuffer {
 
 impl DepthBuffer {
     pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    /// The usage of depth buffers, which can be rendered to, bound and read
+    /// using [`Renderer::read_texture`](crate::renderer::Renderer::read_texture).
+    pub const USAGE: wgpu::TextureUsages = wgpu::TextureUsages::TEXTURE_BINDING
+        .union(wgpu::TextureUsages::COPY_DST)
+        .union(wgpu::TextureUsages::COPY_SRC)
+        .union(wgpu::TextureUsages::RENDER_ATTACHMENT);
+}
+
+impl Bind for DepthBuffer {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        self.texture.binding(index)
+    }
 }
@@ -5,6 +5,7 @@ use crate::buffers::DepthBuffer;
 use crate::canvas::Canvas;
 use crate::color::Bgra8;
 use crate::device::Device;
+use crate::error::Error;
 use crate::renderer::RenderTarget;
 use crate::texture::Texture;
 use crate::transform::ScreenSpace;
@@ -12,6 +13,9 @@ use crate::transform::ScreenSpace;
 #[derive(Debug)]
 pub struct Framebuffer {
     pub texture: Texture,
+    /// The multisampled texture rendered into and resolved to `texture`, if
+    /// the framebuffer was created with a sample count greater than 1.
+    pub multisample: Option<Texture>,
     pub depth: DepthBuffer,
 }
 
@@ -40,10 +44,14 @@ impl RenderTarget for Framebuffer {
     fn zdepth_target(&self) -> &wgpu::TextureView {
         &self.depth.texture.view
     }
+
+    fn multisample_target(&self) -> Option<&wgpu::TextureView> {
+        self.multisample.as_ref().map(|texture| &texture.view)
+    }
 }
 
 impl Bind for Framebuffer {
-    fn binding(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::TextureView(&self.texture.view),
@@ -54,23 +62,23 @@ impl Bind for Framebuffer {
 impl Canvas for Framebuffer {
     type Color = Bgra8;
 
-    fn clear(&self, color: Self::Color, device: &mut Device, encoder: &mut wgpu::CommandEncoder) {
-        Texture::clear(&self.texture, color, device, encoder);
-        Texture::clear(&self.depth.texture, 0f32, device, encoder);
+    fn clear(&self, color: Self::Color, device: &Device) -> Result<(), Error> {
+        // The depth buffer can't be written by copies, and is cleared by the
+        // render passes using it instead.
+        Texture::clear(&self.texture, color, device)
     }
 
-    fn fill(&self, buf: &[Self::Color], device: &mut Device, encoder: &mut wgpu::CommandEncoder) {
-        Texture::fill(&self.texture, buf, device, encoder);
+    fn fill(&self, buf: &[Self::Color], device: &Device) -> Result<(), Error> {
+        Texture::fill(&self.texture, buf, device)
     }
 
     fn transfer(
         &self,
         buf: &[Self::Color],
         rect: SizedRect<i32, ScreenSpace>,
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    ) {
-        Texture::transfer(&self.texture, buf, rect, device, encoder);
+        device: &Device,
+    ) -> Result<(), Error> {
+        Texture::transfer(&self.texture, buf, rect, device)
     }
 
     fn blit(
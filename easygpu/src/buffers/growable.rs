@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+
+use crate::device::Device;
+use crate::error::Error;
+
+/// Writes `bytes` to `buffer` at byte `offset`, where the first `len` bytes
+/// of `buffer` are in use. If `buffer` is too small, it's replaced by a
+/// buffer with the same usage that is at least twice as large, and the bytes
+/// in use are copied to it.
+///
+/// Queue writes must be aligned to [`wgpu::COPY_BUFFER_ALIGNMENT`], so a
+/// write whose length isn't aligned is padded, which is only allowed if it
+/// doesn't overwrite bytes in use.
+pub(crate) fn write(
+    device: &Device,
+    buffer: &mut wgpu::Buffer,
+    len: u64,
+    offset: u64,
+    bytes: &[u8],
+    label: &str,
+) -> Result<(), Error> {
+    let padded_end = padded_end(len, offset, bytes.len() as u64)?;
+    if bytes.is_empty() {
+        return Ok(());
+    }
+
+    if padded_end > buffer.size() {
+        let grown = device.wgpu.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: padded_end.max(buffer.size() * 2),
+            usage: buffer.usage() | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let copied = wgpu::util::align_to(len, wgpu::COPY_BUFFER_ALIGNMENT).min(buffer.size());
+        if copied > 0 {
+            let mut encoder = device.create_command_encoder();
+            encoder.copy_buffer_to_buffer(buffer, 0, &grown, 0, copied);
+            device.queue.submit(Some(encoder.finish()));
+        }
+        *buffer = grown;
+    }
+
+    device.queue.write_buffer(
+        buffer,
+        offset,
+        &padded(bytes, (padded_end - offset) as usize),
+    );
+    Ok(())
+}
+
+/// Returns the end of a write of `size` bytes at `offset` padded to
+/// [`wgpu::COPY_BUFFER_ALIGNMENT`], where the first `len` bytes are in use, or
+/// [`Error::InvalidBufferWrite`] if the write isn't allowed.
+fn padded_end(len: u64, offset: u64, size: u64) -> Result<u64, Error> {
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+    let end = offset + size;
+    let padded_end = wgpu::util::align_to(end, alignment);
+    if offset > len || offset % alignment != 0 || (padded_end != end && end < len) {
+        return Err(Error::InvalidBufferWrite { offset, len: size });
+    }
+    Ok(padded_end)
+}
+
+/// Returns `bytes` followed by zeros up to `padded_len` bytes.
+fn padded(bytes: &[u8], padded_len: usize) -> Cow<'_, [u8]> {
+    if bytes.len() == padded_len {
+        Cow::Borrowed(bytes)
+    } else {
+        let mut padded = bytes.to_vec();
+        padded.resize(padded_len, 0);
+        Cow::Owned(padded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{padded, padded_end};
+    use crate::error::Error;
+
+    fn assert_rejected(len: u64, offset: u64, size: u64) {
+        assert!(matches!(
+            padded_end(len, offset, size),
+            Err(Error::InvalidBufferWrite { offset: o, len: l }) if o == offset && l == size
+        ));
+    }
+
+    #[test]
+    fn aligned_writes_are_not_padded() {
+        assert_eq!(padded_end(0, 0, 16).unwrap(), 16);
+        assert_eq!(padded_end(16, 8, 4).unwrap(), 12);
+        assert_eq!(padded_end(16, 16, 0).unwrap(), 16);
+        assert_eq!(&*padded(&[1, 2, 3, 4], 4), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn writes_past_the_end_of_the_bytes_in_use_are_rejected() {
+        assert_rejected(8, 12, 4);
+    }
+
+    #[test]
+    fn unaligned_offsets_are_rejected() {
+        // A u16 index written at an odd index.
+        assert_rejected(16, 2, 4);
+        assert_rejected(16, 6, 2);
+    }
+
+    #[test]
+    fn unaligned_writes_at_the_end_are_padded_with_zeros() {
+        // Three u16 indices appended, and three overwriting the last two.
+        assert_eq!(padded_end(8, 8, 6).unwrap(), 16);
+        assert_eq!(padded_end(8, 4, 6).unwrap(), 12);
+        assert_eq!(&*padded(&[1, 2, 3, 4, 5, 6], 8), &[1, 2, 3, 4, 5, 6, 0, 0]);
+    }
+
+    #[test]
+    fn padding_over_bytes_in_use_is_rejected() {
+        assert_rejected(16, 0, 6);
+        assert_rejected(16, 8, 2);
+    }
+}
@@ -1,12 +1,73 @@
+use std::marker::PhantomData;
+
+use crate::buffers::growable;
+use crate::error::Error;
+use crate::renderer::Renderer;
+
+/// A type of vertex index, either `u16` or `u32`.
+pub trait IndexType: bytemuck::Pod {
+    const FORMAT: wgpu::IndexFormat;
+}
+
+impl IndexType for u16 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+}
+
+impl IndexType for u32 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint32;
+}
+
+/// A buffer of vertex indices of type `I`.
 #[derive(Debug)]
-pub struct IndexBuffer {
+pub struct IndexBuffer<I = u16> {
     pub wgpu: wgpu::Buffer,
+    /// The number of indices in the buffer.
     pub elements: u32,
+    _phantom: PhantomData<I>,
 }
 
-impl IndexBuffer {
-    pub fn slice(&self) -> wgpu::BufferSlice {
+impl<I: IndexType> IndexBuffer<I> {
+    pub(crate) fn new(wgpu: wgpu::Buffer, elements: u32) -> Self {
+        Self {
+            wgpu,
+            elements,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The number of indices that fit in the buffer without reallocating it.
+    pub fn capacity(&self) -> u32 {
+        (self.wgpu.size() / std::mem::size_of::<I>() as u64) as u32
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        I::FORMAT
+    }
+
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
         self.wgpu
-            .slice(0..(self.elements as usize * std::mem::size_of::<u16>()) as u64)
+            .slice(0..self.elements as u64 * std::mem::size_of::<I>() as u64)
+    }
+
+    /// Writes `indices` starting at index `offset`, reallocating the buffer
+    /// if they exceed its capacity. See
+    /// [`VertexBuffer::write`](crate::buffers::VertexBuffer::write) for the
+    /// alignment requirements.
+    ///
+    /// Because byte offsets must be a multiple of 4, `offset` must be even
+    /// when writing to an `IndexBuffer<u16>`, and so must the number of
+    /// indices unless the write ends at or past the last index.
+    pub fn write(&mut self, renderer: &Renderer, offset: u32, indices: &[I]) -> Result<(), Error> {
+        let size = std::mem::size_of::<I>() as u64;
+        growable::write(
+            &renderer.device,
+            &mut self.wgpu,
+            self.elements as u64 * size,
+            offset as u64 * size,
+            bytemuck::cast_slice(indices),
+            "easygpu index buffer",
+        )?;
+        self.elements = self.elements.max(offset + indices.len() as u32);
+        Ok(())
     }
 }
@@ -1,11 +1,14 @@
 mod depth;
 mod frame;
+mod growable;
 mod index;
+mod storage;
 mod uniform;
 mod vertex;
 
 pub use self::depth::*;
 pub use self::frame::*;
 pub use self::index::*;
+pub use self::storage::*;
 pub use self::uniform::*;
 pub use self::vertex::*;
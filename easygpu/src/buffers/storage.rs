@@ -0,0 +1,25 @@
+use std::num::NonZeroU64;
+
+use crate::binding::Bind;
+
+/// A storage buffer that shaders can read and, unless bound as read-only,
+/// write. Bound with [`BindingType::StorageBuffer`](crate::binding::BindingType::StorageBuffer).
+#[derive(Debug)]
+pub struct StorageBuffer {
+    pub wgpu: wgpu::Buffer,
+    pub size: usize,
+    pub count: usize,
+}
+
+impl Bind for StorageBuffer {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: index,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &self.wgpu,
+                offset: 0,
+                size: NonZeroU64::new((self.size * self.count) as u64),
+            }),
+        }
+    }
+}
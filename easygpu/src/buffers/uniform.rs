@@ -1,3 +1,4 @@
+use std::marker::PhantomData;
 use std::num::NonZeroU64;
 
 use crate::binding::Bind;
@@ -11,7 +12,7 @@ pub struct UniformBuffer {
 }
 
 impl Bind for UniformBuffer {
-    fn binding(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
@@ -22,3 +23,117 @@ impl Bind for UniformBuffer {
         }
     }
 }
+
+/// A uniform buffer containing many values of `T`, each bound by passing its
+/// offset to [`RenderPassExt::set_binding`](crate::renderer::RenderPassExt::set_binding).
+/// Values are spaced by the device's `min_uniform_buffer_offset_alignment`.
+/// Bound with
+/// [`BindingType::UniformBufferDynamic`](crate::binding::BindingType::UniformBufferDynamic).
+#[derive(Debug)]
+pub struct DynamicUniformBuffer<T> {
+    pub wgpu: wgpu::Buffer,
+    /// The number of bytes between consecutive values.
+    pub stride: u32,
+    pub count: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> DynamicUniformBuffer<T> {
+    pub(crate) fn new(wgpu: wgpu::Buffer, stride: u32, count: usize) -> Self {
+        Self {
+            wgpu,
+            stride,
+            count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the dynamic offset of the value at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn offset(&self, index: usize) -> u32 {
+        assert!(
+            index < self.count,
+            "dynamic uniform index {} out of bounds for {} values",
+            index,
+            self.count
+        );
+        offset_of(index, self.stride)
+    }
+
+    /// Returns the dynamic offset of each value, in order.
+    pub fn offsets(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.count).map(move |index| offset_of(index, self.stride))
+    }
+
+    /// Returns the stride of values aligned to `alignment`, which is the
+    /// device's `min_uniform_buffer_offset_alignment`.
+    pub(crate) fn stride(alignment: u32) -> u32 {
+        wgpu::util::align_to(std::mem::size_of::<T>() as u32, alignment)
+    }
+
+    /// Packs `values` at the alignment of this buffer.
+    pub(crate) fn pack(values: &[T], stride: u32) -> Vec<u8> {
+        let mut bytes = vec![0; values.len().max(1) * stride as usize];
+        for (index, value) in values.iter().enumerate() {
+            let offset = offset_of(index, stride) as usize;
+            let value = bytemuck::bytes_of(value);
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+        }
+        bytes
+    }
+}
+
+/// Returns the offset of the value at `index` in a buffer of values `stride`
+/// bytes apart.
+fn offset_of(index: usize, stride: u32) -> u32 {
+    index as u32 * stride
+}
+
+impl<T> Bind for DynamicUniformBuffer<T> {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
+        wgpu::BindGroupEntry {
+            binding: index,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &self.wgpu,
+                offset: 0,
+                size: NonZeroU64::new(std::mem::size_of::<T>() as u64),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{offset_of, DynamicUniformBuffer};
+
+    fn assert_packed<T: bytemuck::Pod>(values: &[T], alignment: u32) {
+        let stride = DynamicUniformBuffer::<T>::stride(alignment);
+        let bytes = DynamicUniformBuffer::pack(values, stride);
+        assert_eq!(bytes.len(), values.len() * stride as usize);
+
+        let size = std::mem::size_of::<T>();
+        for (index, value) in values.iter().enumerate() {
+            let offset = offset_of(index, stride) as usize;
+            assert_eq!(offset % alignment as usize, 0);
+            assert_eq!(&bytes[offset..offset + size], bytemuck::bytes_of(value));
+            assert!(bytes[offset + size..offset + stride as usize]
+                .iter()
+                .all(|&byte| byte == 0));
+        }
+    }
+
+    #[test]
+    fn values_are_packed_at_aligned_offsets() {
+        assert_eq!(DynamicUniformBuffer::<[f32; 4]>::stride(256), 256);
+        assert_eq!(DynamicUniformBuffer::<[f32; 96]>::stride(256), 512);
+        assert_eq!(DynamicUniformBuffer::<[f32; 16]>::stride(64), 64);
+
+        assert_packed(&[1f32, 2., 3.], 256);
+        assert_packed(&[[1f32; 20], [2.; 20], [3.; 20]], 64);
+        assert_packed(&[[1u32; 96], [2; 96]], 256);
+        assert_packed(&[[1u32; 64], [2; 64]], 256);
+    }
+}
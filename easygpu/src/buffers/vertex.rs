@@ -1,27 +1,67 @@
+use std::marker::PhantomData;
+
+use crate::buffers::growable;
+use crate::error::Error;
 use crate::prelude::BindingGroup;
-use crate::renderer::{Draw, RenderPassExt};
+use crate::renderer::{Draw, RenderPassExt, Renderer};
 
+/// A buffer of vertices of type `T`.
 #[derive(Debug)]
-pub struct VertexBuffer {
-    pub size: u32,
+pub struct VertexBuffer<T> {
     pub wgpu: wgpu::Buffer,
+    /// The number of vertices in the buffer.
+    pub count: u32,
+    _phantom: PhantomData<T>,
 }
 
-impl Draw for VertexBuffer {
+impl<T: bytemuck::Pod> Draw for VertexBuffer<T> {
     fn draw<'a>(&'a self, binding: &'a BindingGroup, pass: &mut wgpu::RenderPass<'a>) {
-        // TODO: If we attempt to draw more vertices than exist in the buffer, because
-        // 'size' was guessed wrong, we get a wgpu error. We should somehow try to
-        // get the pipeline layout to know here if the buffer we're trying to draw
-        // is the right size. Another option is to create buffers from the pipeline,
-        // so that we can check at creation time whether the data passed in matches
-        // the format.
+        // TODO: We can't yet check that `T` matches the vertex layout of the
+        // pipeline that's set. Creating buffers from the pipeline would let us
+        // check at creation time whether the data passed in matches the
+        // format.
         pass.set_binding(binding, &[]);
         pass.draw_buffer(self);
     }
 }
 
-impl VertexBuffer {
-    pub fn slice(&self) -> wgpu::BufferSlice {
-        self.wgpu.slice(0..self.size as u64)
+impl<T: bytemuck::Pod> VertexBuffer<T> {
+    pub(crate) fn new(wgpu: wgpu::Buffer, count: u32) -> Self {
+        Self {
+            wgpu,
+            count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The number of vertices that fit in the buffer without reallocating it.
+    pub fn capacity(&self) -> u32 {
+        (self.wgpu.size() / std::mem::size_of::<T>() as u64) as u32
+    }
+
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.wgpu
+            .slice(0..self.count as u64 * std::mem::size_of::<T>() as u64)
+    }
+
+    /// Writes `vertices` starting at vertex `offset`, which must not be past
+    /// the end of the buffer's vertices. The buffer is reallocated if the
+    /// vertices exceed its capacity, so binding groups and draws must use the
+    /// buffer after it is written.
+    ///
+    /// The byte offset must be a multiple of 4, and unless the write ends at
+    /// or past the last vertex, so must the byte length.
+    pub fn write(&mut self, renderer: &Renderer, offset: u32, vertices: &[T]) -> Result<(), Error> {
+        let size = std::mem::size_of::<T>() as u64;
+        growable::write(
+            &renderer.device,
+            &mut self.wgpu,
+            self.count as u64 * size,
+            offset as u64 * size,
+            bytemuck::cast_slice(vertices),
+            "easygpu vertex buffer",
+        )?;
+        self.count = self.count.max(offset + vertices.len() as u32);
+        Ok(())
     }
 }
@@ -1,20 +1,20 @@
 use figures::SizedRect;
 
 use crate::device::Device;
+use crate::error::Error;
 use crate::transform::ScreenSpace;
 
 pub trait Canvas {
     type Color;
 
-    fn clear(&self, color: Self::Color, device: &mut Device, encoder: &mut wgpu::CommandEncoder);
-    fn fill(&self, buf: &[Self::Color], device: &mut Device, encoder: &mut wgpu::CommandEncoder);
+    fn clear(&self, color: Self::Color, device: &Device) -> Result<(), Error>;
+    fn fill(&self, buf: &[Self::Color], device: &Device) -> Result<(), Error>;
     fn transfer(
         &self,
         buf: &[Self::Color],
         r: SizedRect<i32, ScreenSpace>,
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    );
+        device: &Device,
+    ) -> Result<(), Error>;
     fn blit(
         &self,
         from: SizedRect<u32, ScreenSpace>,
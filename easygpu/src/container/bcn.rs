@@ -0,0 +1,267 @@
+//! Decoders for the unsigned BC1 to BC5 block compressed formats, used when
+//! the adapter doesn't support them. The signed BC4 and BC5 formats, BC6H and
+//! BC7 can't be decoded.
+
+use std::convert::TryInto;
+
+use wgpu::TextureFormat;
+
+use crate::error::Error;
+
+/// Returns the uncompressed format that texels of `format` are decoded to, or
+/// `None` if `format` can't be decoded.
+pub fn decoded_format(format: TextureFormat) -> Option<TextureFormat> {
+    match format {
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc2RgbaUnorm | TextureFormat::Bc3RgbaUnorm => {
+            Some(TextureFormat::Rgba8Unorm)
+        }
+        TextureFormat::Bc1RgbaUnormSrgb
+        | TextureFormat::Bc2RgbaUnormSrgb
+        | TextureFormat::Bc3RgbaUnormSrgb => Some(TextureFormat::Rgba8UnormSrgb),
+        TextureFormat::Bc4RUnorm => Some(TextureFormat::R8Unorm),
+        TextureFormat::Bc5RgUnorm => Some(TextureFormat::Rg8Unorm),
+        _ => None,
+    }
+}
+
+/// Decodes the blocks of a `width` by `height` image of `format` into tightly
+/// packed texels of [`decoded_format`]`(format)`, or returns
+/// [`Error::UnsupportedTextureFormat`] if `format` can't be decoded.
+pub fn decode(
+    format: TextureFormat,
+    width: u32,
+    height: u32,
+    blocks: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let (block_size, channels) = match format {
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc1RgbaUnormSrgb => (8, 4),
+        TextureFormat::Bc2RgbaUnorm
+        | TextureFormat::Bc2RgbaUnormSrgb
+        | TextureFormat::Bc3RgbaUnorm
+        | TextureFormat::Bc3RgbaUnormSrgb => (16, 4),
+        TextureFormat::Bc4RUnorm => (8, 1),
+        TextureFormat::Bc5RgUnorm => (16, 2),
+        _ => return Err(Error::UnsupportedTextureFormat(format)),
+    };
+    let (width, height) = (width as usize, height as usize);
+    let columns = width.div_ceil(4);
+
+    let mut texels = vec![0; width * height * channels];
+    for (index, block) in blocks.chunks_exact(block_size).enumerate() {
+        let decoded = match format {
+            TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc1RgbaUnormSrgb => {
+                decode_color(block, true)
+            }
+            TextureFormat::Bc2RgbaUnorm | TextureFormat::Bc2RgbaUnormSrgb => {
+                let mut decoded = decode_color(&block[8..], false);
+                let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+                for (texel, rgba) in decoded.iter_mut().enumerate() {
+                    rgba[3] = ((alpha >> (texel * 4)) & 0xf) as u8 * 17;
+                }
+                decoded
+            }
+            TextureFormat::Bc3RgbaUnorm | TextureFormat::Bc3RgbaUnormSrgb => {
+                let mut decoded = decode_color(&block[8..], false);
+                for (rgba, alpha) in decoded.iter_mut().zip(decode_channel(&block[..8])) {
+                    rgba[3] = alpha;
+                }
+                decoded
+            }
+            TextureFormat::Bc4RUnorm => {
+                let mut decoded = [[0; 4]; 16];
+                for (texel, red) in decoded.iter_mut().zip(decode_channel(block)) {
+                    texel[0] = red;
+                }
+                decoded
+            }
+            _ => {
+                let mut decoded = [[0; 4]; 16];
+                let red = decode_channel(&block[..8]);
+                let green = decode_channel(&block[8..]);
+                for (texel, rg) in decoded.iter_mut().enumerate() {
+                    rg[0] = red[texel];
+                    rg[1] = green[texel];
+                }
+                decoded
+            }
+        };
+
+        // Blocks on the right and bottom edges may extend past the image.
+        let (block_x, block_y) = (index % columns * 4, index / columns * 4);
+        for (texel, value) in decoded.iter().enumerate() {
+            let (x, y) = (block_x + texel % 4, block_y + texel / 4);
+            if x < width && y < height {
+                let start = (y * width + x) * channels;
+                texels[start..start + channels].copy_from_slice(&value[..channels]);
+            }
+        }
+    }
+    Ok(texels)
+}
+
+/// Decodes the 8 byte color block shared by BC1, BC2 and BC3 into the RGBA
+/// values of its 16 texels. Only BC1 blocks use the three color mode, which
+/// has a transparent fourth color.
+fn decode_color(block: &[u8], bc1: bool) -> [[u8; 4]; 16] {
+    let color0 = u16::from_le_bytes([block[0], block[1]]);
+    let color1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let (c0, c1) = (rgb565(color0), rgb565(color1));
+    let mix = |a: u8, b: u8, a_weight: u16, b_weight: u16| {
+        let total = a_weight + b_weight;
+        ((u16::from(a) * a_weight + u16::from(b) * b_weight + total / 2) / total) as u8
+    };
+    let mut palette = [[0; 4]; 4];
+    palette[0] = [c0[0], c0[1], c0[2], 255];
+    palette[1] = [c1[0], c1[1], c1[2], 255];
+    if color0 > color1 || !bc1 {
+        for channel in 0..3 {
+            palette[2][channel] = mix(c0[channel], c1[channel], 2, 1);
+            palette[3][channel] = mix(c0[channel], c1[channel], 1, 2);
+        }
+        palette[2][3] = 255;
+        palette[3][3] = 255;
+    } else {
+        for channel in 0..3 {
+            palette[2][channel] = mix(c0[channel], c1[channel], 1, 1);
+        }
+        palette[2][3] = 255;
+    }
+
+    let mut texels = [[0; 4]; 16];
+    for (texel, rgba) in texels.iter_mut().enumerate() {
+        *rgba = palette[((indices >> (texel * 2)) & 0b11) as usize];
+    }
+    texels
+}
+
+/// Decodes an 8 byte single channel block, used by BC3's alpha channel and by
+/// BC4 and BC5, into the values of its 16 texels.
+fn decode_channel(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (u16::from(block[0]), u16::from(block[1]));
+    let mut palette = [0; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for index in 1..7 {
+            palette[index + 1] = (((7 - index as u16) * a0 + index as u16 * a1 + 3) / 7) as u8;
+        }
+    } else {
+        for index in 1..5 {
+            palette[index + 1] = (((5 - index as u16) * a0 + index as u16 * a1 + 2) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    let mut texels = [0; 16];
+    for (texel, value) in texels.iter_mut().enumerate() {
+        *value = palette[((indices >> (texel * 3)) & 0b111) as usize];
+    }
+    texels
+}
+
+/// Expands a packed RGB 5:6:5 color to 8 bits per channel.
+fn rgb565(color: u16) -> [u8; 3] {
+    let red = (color >> 11) & 0x1f;
+    let green = (color >> 5) & 0x3f;
+    let blue = color & 0x1f;
+    [
+        ((red << 3) | (red >> 2)) as u8,
+        ((green << 2) | (green >> 4)) as u8,
+        ((blue << 3) | (blue >> 2)) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::TextureFormat;
+
+    use super::decode;
+    use crate::error::Error;
+
+    /// Indices selecting each of the 8 palette entries of a single channel
+    /// block in turn, twice.
+    const CHANNEL_INDICES: [u8; 6] = [0x88, 0xc6, 0xfa, 0x88, 0xc6, 0xfa];
+
+    #[test]
+    fn bc1_three_color_mode_has_a_transparent_color() {
+        // Blue is less than red, so the block uses the three color mode. Each
+        // row selects the palette entries in turn.
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0xe4, 0xe4, 0xe4, 0xe4];
+        let row = [0, 0, 255, 255, 255, 0, 0, 255, 128, 0, 128, 255, 0, 0, 0, 0];
+
+        let texels = decode(TextureFormat::Bc1RgbaUnorm, 4, 4, &block).unwrap();
+        assert_eq!(texels, row.repeat(4));
+    }
+
+    #[test]
+    fn bc1_four_color_mode_is_opaque() {
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0xe4, 0xe4, 0xe4, 0xe4];
+        let row = [
+            255, 0, 0, 255, 0, 0, 255, 255, 170, 0, 85, 255, 85, 0, 170, 255,
+        ];
+
+        let texels = decode(TextureFormat::Bc1RgbaUnorm, 4, 4, &block).unwrap();
+        assert_eq!(texels, row.repeat(4));
+    }
+
+    #[test]
+    fn bc2_alpha_has_four_bits() {
+        // Texel n has an alpha of n, followed by an opaque white color block.
+        let block = [
+            0x10, 0x32, 0x54, 0x76, 0x98, 0xba, 0xdc, 0xfe, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+
+        let texels = decode(TextureFormat::Bc2RgbaUnorm, 4, 4, &block).unwrap();
+        let alpha = texels
+            .chunks_exact(4)
+            .map(|rgba| rgba[3])
+            .collect::<Vec<_>>();
+        assert_eq!(alpha, (0..16).map(|n| n * 17).collect::<Vec<_>>());
+        assert!(texels.chunks_exact(4).all(|rgba| rgba[..3] == [255; 3]));
+    }
+
+    #[test]
+    fn bc4_interpolates_six_values_when_the_first_endpoint_is_larger() {
+        let mut block = vec![255, 0];
+        block.extend_from_slice(&CHANNEL_INDICES);
+        let palette = [255, 0, 219, 182, 146, 109, 73, 36];
+
+        let texels = decode(TextureFormat::Bc4RUnorm, 4, 4, &block).unwrap();
+        assert_eq!(texels, palette.repeat(2));
+    }
+
+    #[test]
+    fn bc4_interpolates_four_values_and_adds_the_extremes_otherwise() {
+        let mut block = vec![0, 255];
+        block.extend_from_slice(&CHANNEL_INDICES);
+        let palette = [0, 255, 51, 102, 153, 204, 0, 255];
+
+        let texels = decode(TextureFormat::Bc4RUnorm, 4, 4, &block).unwrap();
+        assert_eq!(texels, palette.repeat(2));
+    }
+
+    #[test]
+    fn blocks_are_cropped_to_the_image() {
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0xe4, 0xe4, 0xe4, 0xe4];
+
+        let texels = decode(TextureFormat::Bc1RgbaUnorm, 3, 1, &block).unwrap();
+        assert_eq!(texels, [0, 0, 255, 255, 255, 0, 0, 255, 128, 0, 128, 255]);
+    }
+
+    #[test]
+    fn signed_formats_are_unsupported() {
+        for format in [TextureFormat::Bc4RSnorm, TextureFormat::Bc5RgSnorm] {
+            assert!(matches!(
+                decode(format, 4, 4, &[0; 16]),
+                Err(Error::UnsupportedTextureFormat(unsupported)) if unsupported == format
+            ));
+        }
+    }
+}
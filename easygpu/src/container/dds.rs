@@ -0,0 +1,147 @@
+use ddsfile::{Caps2, D3D10ResourceDimension, D3DFormat, Dds, DxgiFormat, MiscFlag};
+use figures::Size;
+use wgpu::{TextureFormat, TextureViewDimension};
+
+use super::ContainerImage;
+use crate::device::Device;
+use crate::error::Error;
+use crate::texture::{TexelLayout, Texture, TextureOptions};
+
+impl Device {
+    /// Loads a DDS file into a new texture with the file's format, mip
+    /// levels, array layers and cube faces. The texture's usage and label are
+    /// taken from `options`. Files without a DX10 header don't specify whether
+    /// their colors are sRGB encoded, so [`TextureOptions::srgb`] selects the
+    /// format's sRGB variant.
+    ///
+    /// Compressed formats that the device doesn't support are decompressed on
+    /// the CPU, which is only supported for the unsigned BC1 to BC5 formats.
+    /// Other formats return [`Error::UnsupportedTextureFormat`]. Enable
+    /// compressed formats using [`DeviceOptions::with_texture_compression`](crate::device::DeviceOptions::with_texture_compression).
+    pub fn create_texture_from_dds(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        let dds = Dds::read(bytes).map_err(|err| Error::TextureContainer(err.to_string()))?;
+        let format = if let Some(format) = dds.get_dxgi_format() {
+            dxgi_format(format)
+        } else if let Some(format) = dds.get_d3d_format() {
+            d3d_format(format).map(|format| {
+                if options.srgb {
+                    format.add_srgb_suffix()
+                } else {
+                    format
+                }
+            })
+        } else {
+            None
+        }
+        .ok_or_else(|| Error::TextureContainer(String::from("unsupported DDS format")))?;
+
+        let size = Size::new(dds.get_width(), dds.get_height());
+        let (layers, view_dimension) = match &dds.header10 {
+            Some(header) if header.resource_dimension == D3D10ResourceDimension::Texture3D => {
+                (dds.get_depth(), TextureViewDimension::D3)
+            }
+            Some(header) if header.misc_flag.contains(MiscFlag::TEXTURECUBE) => {
+                if header.array_size > 1 {
+                    (header.array_size * 6, TextureViewDimension::CubeArray)
+                } else {
+                    (6, TextureViewDimension::Cube)
+                }
+            }
+            Some(header) if header.array_size > 1 => {
+                (header.array_size, TextureViewDimension::D2Array)
+            }
+            Some(_) => (1, TextureViewDimension::D2),
+            None if dds.header.caps2.contains(Caps2::CUBEMAP) => (6, TextureViewDimension::Cube),
+            None if dds.header.caps2.contains(Caps2::VOLUME) => {
+                (dds.get_depth(), TextureViewDimension::D3)
+            }
+            None => (1, TextureViewDimension::D2),
+        };
+
+        // DDS files store every mip level of each layer before the next layer,
+        // while `ContainerImage` stores every layer of each mip level first.
+        let mut image = ContainerImage {
+            format,
+            size,
+            layers,
+            view_dimension,
+            levels: vec![Vec::new(); dds.get_num_mipmap_levels().max(1) as usize],
+        };
+        let layer_images = if view_dimension == TextureViewDimension::D3 {
+            1
+        } else {
+            layers
+        };
+        let mut data = dds.data.as_slice();
+        for _ in 0..layer_images {
+            for mip_level in 0..image.levels.len() {
+                let (mip_size, depth) = image.mip_level_size(mip_level);
+                let depth = if view_dimension == TextureViewDimension::D3 {
+                    depth
+                } else {
+                    1
+                };
+                let len = TexelLayout::new(format, mip_size)?.byte_len() * depth as usize;
+                if data.len() < len {
+                    return Err(Error::TextureContainer(String::from(
+                        "the DDS file is missing texels",
+                    )));
+                }
+                let (texels, rest) = data.split_at(len);
+                image.levels[mip_level].extend_from_slice(texels);
+                data = rest;
+            }
+        }
+
+        self.create_texture_from_container(image, options, "dds")
+    }
+}
+
+/// Returns the texture format matching a DDS file's DXGI format.
+fn dxgi_format(format: DxgiFormat) -> Option<TextureFormat> {
+    let format = match format {
+        DxgiFormat::R8_UNorm => TextureFormat::R8Unorm,
+        DxgiFormat::R8G8_UNorm => TextureFormat::Rg8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm => TextureFormat::Rgba8Unorm,
+        DxgiFormat::R8G8B8A8_UNorm_sRGB => TextureFormat::Rgba8UnormSrgb,
+        DxgiFormat::B8G8R8A8_UNorm => TextureFormat::Bgra8Unorm,
+        DxgiFormat::B8G8R8A8_UNorm_sRGB => TextureFormat::Bgra8UnormSrgb,
+        DxgiFormat::R16_Float => TextureFormat::R16Float,
+        DxgiFormat::R16G16B16A16_Float => TextureFormat::Rgba16Float,
+        DxgiFormat::R32_Float => TextureFormat::R32Float,
+        DxgiFormat::R32G32B32A32_Float => TextureFormat::Rgba32Float,
+        DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm => TextureFormat::Bc1RgbaUnorm,
+        DxgiFormat::BC1_UNorm_sRGB => TextureFormat::Bc1RgbaUnormSrgb,
+        DxgiFormat::BC2_Typeless | DxgiFormat::BC2_UNorm => TextureFormat::Bc2RgbaUnorm,
+        DxgiFormat::BC2_UNorm_sRGB => TextureFormat::Bc2RgbaUnormSrgb,
+        DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm => TextureFormat::Bc3RgbaUnorm,
+        DxgiFormat::BC3_UNorm_sRGB => TextureFormat::Bc3RgbaUnormSrgb,
+        DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm => TextureFormat::Bc4RUnorm,
+        DxgiFormat::BC4_SNorm => TextureFormat::Bc4RSnorm,
+        DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm => TextureFormat::Bc5RgUnorm,
+        DxgiFormat::BC5_SNorm => TextureFormat::Bc5RgSnorm,
+        DxgiFormat::BC6H_Typeless | DxgiFormat::BC6H_UF16 => TextureFormat::Bc6hRgbUfloat,
+        DxgiFormat::BC6H_SF16 => TextureFormat::Bc6hRgbFloat,
+        DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm => TextureFormat::Bc7RgbaUnorm,
+        DxgiFormat::BC7_UNorm_sRGB => TextureFormat::Bc7RgbaUnormSrgb,
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Returns the linear texture format matching a legacy DDS file's format.
+fn d3d_format(format: D3DFormat) -> Option<TextureFormat> {
+    let format = match format {
+        D3DFormat::A8B8G8R8 => TextureFormat::Rgba8Unorm,
+        D3DFormat::A8R8G8B8 => TextureFormat::Bgra8Unorm,
+        D3DFormat::DXT1 => TextureFormat::Bc1RgbaUnorm,
+        D3DFormat::DXT2 | D3DFormat::DXT3 => TextureFormat::Bc2RgbaUnorm,
+        D3DFormat::DXT4 | D3DFormat::DXT5 => TextureFormat::Bc3RgbaUnorm,
+        _ => return None,
+    };
+    Some(format)
+}
@@ -0,0 +1,136 @@
+use figures::Size;
+use ktx2::{Format, Reader};
+use wgpu::{AstcBlock, AstcChannel, TextureFormat, TextureViewDimension};
+
+use super::ContainerImage;
+use crate::device::Device;
+use crate::error::Error;
+use crate::texture::{Texture, TextureOptions};
+
+impl Device {
+    /// Loads a KTX2 file into a new texture with the file's format, mip
+    /// levels, array layers and cube faces. The texture's usage and label are
+    /// taken from `options`.
+    ///
+    /// Compressed formats that the device doesn't support are decompressed on
+    /// the CPU, which is only supported for the unsigned BC1 to BC5 formats.
+    /// Other formats return [`Error::UnsupportedTextureFormat`]. Enable
+    /// compressed formats using [`DeviceOptions::with_texture_compression`](crate::device::DeviceOptions::with_texture_compression).
+    /// Supercompressed files are not supported.
+    pub fn create_texture_from_ktx2(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        let reader = Reader::new(bytes).map_err(|err| Error::TextureContainer(err.to_string()))?;
+        let header = reader.header();
+        if let Some(scheme) = header.supercompression_scheme {
+            return Err(Error::TextureContainer(format!(
+                "unsupported supercompression scheme {:?}",
+                scheme
+            )));
+        }
+        let format = header.format.and_then(texture_format).ok_or_else(|| {
+            Error::TextureContainer(format!("unsupported KTX2 format {:?}", header.format))
+        })?;
+
+        let array_layers = header.layer_count.max(1);
+        let (layers, view_dimension) = if header.pixel_depth > 1 {
+            (header.pixel_depth, TextureViewDimension::D3)
+        } else if header.face_count == 6 {
+            if header.layer_count > 0 {
+                (array_layers * 6, TextureViewDimension::CubeArray)
+            } else {
+                (6, TextureViewDimension::Cube)
+            }
+        } else if header.layer_count > 0 {
+            (array_layers, TextureViewDimension::D2Array)
+        } else {
+            (1, TextureViewDimension::D2)
+        };
+
+        let image = ContainerImage {
+            format,
+            size: Size::new(header.pixel_width, header.pixel_height.max(1)),
+            layers,
+            view_dimension,
+            levels: reader.levels().map(<[u8]>::to_vec).collect(),
+        };
+        self.create_texture_from_container(image, options, "ktx2")
+    }
+}
+
+/// Returns the texture format matching a KTX2 file's Vulkan format.
+fn texture_format(format: Format) -> Option<TextureFormat> {
+    let format = match format {
+        Format::R8_UNORM => TextureFormat::R8Unorm,
+        Format::R8G8_UNORM => TextureFormat::Rg8Unorm,
+        Format::R8G8B8A8_UNORM => TextureFormat::Rgba8Unorm,
+        Format::R8G8B8A8_SRGB => TextureFormat::Rgba8UnormSrgb,
+        Format::B8G8R8A8_UNORM => TextureFormat::Bgra8Unorm,
+        Format::B8G8R8A8_SRGB => TextureFormat::Bgra8UnormSrgb,
+        Format::R16_SFLOAT => TextureFormat::R16Float,
+        Format::R16G16B16A16_SFLOAT => TextureFormat::Rgba16Float,
+        Format::R32_SFLOAT => TextureFormat::R32Float,
+        Format::R32G32B32A32_SFLOAT => TextureFormat::Rgba32Float,
+        Format::BC1_RGBA_UNORM_BLOCK | Format::BC1_RGB_UNORM_BLOCK => TextureFormat::Bc1RgbaUnorm,
+        Format::BC1_RGBA_SRGB_BLOCK | Format::BC1_RGB_SRGB_BLOCK => TextureFormat::Bc1RgbaUnormSrgb,
+        Format::BC2_UNORM_BLOCK => TextureFormat::Bc2RgbaUnorm,
+        Format::BC2_SRGB_BLOCK => TextureFormat::Bc2RgbaUnormSrgb,
+        Format::BC3_UNORM_BLOCK => TextureFormat::Bc3RgbaUnorm,
+        Format::BC3_SRGB_BLOCK => TextureFormat::Bc3RgbaUnormSrgb,
+        Format::BC4_UNORM_BLOCK => TextureFormat::Bc4RUnorm,
+        Format::BC4_SNORM_BLOCK => TextureFormat::Bc4RSnorm,
+        Format::BC5_UNORM_BLOCK => TextureFormat::Bc5RgUnorm,
+        Format::BC5_SNORM_BLOCK => TextureFormat::Bc5RgSnorm,
+        Format::BC6H_UFLOAT_BLOCK => TextureFormat::Bc6hRgbUfloat,
+        Format::BC6H_SFLOAT_BLOCK => TextureFormat::Bc6hRgbFloat,
+        Format::BC7_UNORM_BLOCK => TextureFormat::Bc7RgbaUnorm,
+        Format::BC7_SRGB_BLOCK => TextureFormat::Bc7RgbaUnormSrgb,
+        Format::ETC2_R8G8B8_UNORM_BLOCK => TextureFormat::Etc2Rgb8Unorm,
+        Format::ETC2_R8G8B8_SRGB_BLOCK => TextureFormat::Etc2Rgb8UnormSrgb,
+        Format::ETC2_R8G8B8A1_UNORM_BLOCK => TextureFormat::Etc2Rgb8A1Unorm,
+        Format::ETC2_R8G8B8A1_SRGB_BLOCK => TextureFormat::Etc2Rgb8A1UnormSrgb,
+        Format::ETC2_R8G8B8A8_UNORM_BLOCK => TextureFormat::Etc2Rgba8Unorm,
+        Format::ETC2_R8G8B8A8_SRGB_BLOCK => TextureFormat::Etc2Rgba8UnormSrgb,
+        Format::EAC_R11_UNORM_BLOCK => TextureFormat::EacR11Unorm,
+        Format::EAC_R11_SNORM_BLOCK => TextureFormat::EacR11Snorm,
+        Format::EAC_R11G11_UNORM_BLOCK => TextureFormat::EacRg11Unorm,
+        Format::EAC_R11G11_SNORM_BLOCK => TextureFormat::EacRg11Snorm,
+        _ => return astc_format(format),
+    };
+    Some(format)
+}
+
+/// Returns the texture format matching a KTX2 file's ASTC format. The Vulkan
+/// ASTC formats alternate between the UNORM and SRGB variants of each block
+/// size.
+fn astc_format(format: Format) -> Option<TextureFormat> {
+    const BLOCKS: [AstcBlock; 14] = [
+        AstcBlock::B4x4,
+        AstcBlock::B5x4,
+        AstcBlock::B5x5,
+        AstcBlock::B6x5,
+        AstcBlock::B6x6,
+        AstcBlock::B8x5,
+        AstcBlock::B8x6,
+        AstcBlock::B8x8,
+        AstcBlock::B10x5,
+        AstcBlock::B10x6,
+        AstcBlock::B10x8,
+        AstcBlock::B10x10,
+        AstcBlock::B12x10,
+        AstcBlock::B12x12,
+    ];
+    let index = format
+        .0
+        .get()
+        .checked_sub(Format::ASTC_4x4_UNORM_BLOCK.0.get())? as usize;
+    let block = *BLOCKS.get(index / 2)?;
+    let channel = if index % 2 == 0 {
+        AstcChannel::Unorm
+    } else {
+        AstcChannel::UnormSrgb
+    };
+    Some(TextureFormat::Astc { block, channel })
+}
@@ -0,0 +1,171 @@
+use figures::{Point, Size, SizedRect};
+use wgpu::{TextureFormat, TextureViewDimension};
+
+use crate::device::{label_or, Device};
+use crate::error::Error;
+use crate::renderer::Renderer;
+use crate::texture::{TexelLayout, Texture, TextureDescriptor, TextureOptions};
+use crate::transform::ScreenSpace;
+
+mod bcn;
+#[cfg(feature = "ddsfile")]
+mod dds;
+#[cfg(feature = "ktx2")]
+mod ktx;
+
+/// The texels of a texture read from a container file.
+#[derive(Debug)]
+struct ContainerImage {
+    format: TextureFormat,
+    size: Size<u32, ScreenSpace>,
+    /// The number of array layers, cube faces or depth slices.
+    layers: u32,
+    view_dimension: TextureViewDimension,
+    /// The texels of each mip level. Each level contains an image for each of
+    /// its layers or depth slices, one after another.
+    levels: Vec<Vec<u8>>,
+}
+
+impl ContainerImage {
+    fn dimension(&self) -> wgpu::TextureDimension {
+        if self.view_dimension == TextureViewDimension::D3 {
+            wgpu::TextureDimension::D3
+        } else {
+            wgpu::TextureDimension::D2
+        }
+    }
+
+    fn descriptor(&self, usage: wgpu::TextureUsages) -> TextureDescriptor<'static> {
+        TextureDescriptor {
+            label: None,
+            size: self.size,
+            layers: self.layers,
+            view_dimension: self.view_dimension,
+            format: self.format,
+            usage,
+            mip_level_count: self.levels.len() as u32,
+            sample_count: 1,
+        }
+    }
+
+    /// Returns the size of mip level `mip_level`, and the number of images it
+    /// contains.
+    fn mip_level_size(&self, mip_level: usize) -> (Size<u32, ScreenSpace>, u32) {
+        let extent = wgpu::Extent3d {
+            width: self.size.width,
+            height: self.size.height,
+            depth_or_array_layers: self.layers,
+        }
+        .mip_level_size(mip_level as u32, self.dimension());
+        (
+            Size::new(extent.width, extent.height),
+            extent.depth_or_array_layers,
+        )
+    }
+
+    /// Returns each image of mip level `mip_level`, or an error if the level
+    /// is missing texels.
+    fn images(&self, mip_level: usize) -> Result<std::slice::ChunksExact<'_, u8>, Error> {
+        let (size, images) = self.mip_level_size(mip_level);
+        let image_len = TexelLayout::new(self.format, size)?.byte_len();
+        let level = &self.levels[mip_level];
+        if level.len() < image_len * images as usize {
+            return Err(Error::TextureContainer(format!(
+                "mip level {} is missing texels",
+                mip_level
+            )));
+        }
+        Ok(level[..image_len * images as usize].chunks_exact(image_len))
+    }
+
+    /// Decodes the texels on the CPU into an uncompressed format.
+    fn decompress(self) -> Result<Self, Error> {
+        let format =
+            bcn::decoded_format(self.format).ok_or(Error::UnsupportedTextureFormat(self.format))?;
+        let levels = (0..self.levels.len())
+            .map(|mip_level| {
+                let (size, _) = self.mip_level_size(mip_level);
+                Ok(self
+                    .images(mip_level)?
+                    .map(|image| bcn::decode(self.format, size.width, size.height, image))
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .concat())
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self {
+            format,
+            levels,
+            ..self
+        })
+    }
+}
+
+impl Device {
+    /// Creates a texture from the texels read from a container, decompressing
+    /// them on the CPU if the device doesn't support their format.
+    fn create_texture_from_container(
+        &self,
+        image: ContainerImage,
+        options: &TextureOptions,
+        kind: &str,
+    ) -> Result<Texture, Error> {
+        let image = if self.supports_format(image.format) {
+            image
+        } else {
+            image.decompress()?
+        };
+
+        let label = label_or(options.label.as_deref(), || {
+            format!(
+                "easygpu {} texture {}x{}",
+                kind, image.size.width, image.size.height
+            )
+        });
+        let mut desc = image.descriptor(options.usage | wgpu::TextureUsages::COPY_DST);
+        desc.label = Some(&label);
+        let texture = self.create_texture_with_descriptor(&desc);
+
+        for mip_level in 0..image.levels.len() {
+            let (size, _) = image.mip_level_size(mip_level);
+            for (layer, texels) in image.images(mip_level)?.enumerate() {
+                Texture::write_bytes(
+                    &texture,
+                    texels,
+                    SizedRect::new(Point::default(), size),
+                    layer as u32,
+                    mip_level as u32,
+                    self,
+                )?;
+            }
+        }
+
+        Ok(texture)
+    }
+}
+
+#[cfg(feature = "ktx2")]
+impl Renderer {
+    /// Loads a KTX2 file into a new texture. See
+    /// [`Device::create_texture_from_ktx2`].
+    pub fn texture_from_ktx2(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        self.device.create_texture_from_ktx2(bytes, options)
+    }
+}
+
+#[cfg(feature = "ddsfile")]
+impl Renderer {
+    /// Loads a DDS file into a new texture. See
+    /// [`Device::create_texture_from_dds`].
+    pub fn texture_from_dds(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        self.device.create_texture_from_dds(bytes, options)
+    }
+}
@@ -1,76 +1,251 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
 use figures::{Pixels, Size};
 use wgpu::util::DeviceExt;
-use wgpu::{
-    CompositeAlphaMode, FilterMode, MultisampleState, SubmissionIndex, TextureFormat, TextureUsages,
-};
+use wgpu::{FilterMode, MultisampleState, SubmissionIndex, TextureFormat, TextureUsages};
 
 use crate::binding::{Bind, Binding, BindingGroup, BindingGroupLayout};
-use crate::buffers::{DepthBuffer, Framebuffer, IndexBuffer, UniformBuffer, VertexBuffer};
+use crate::buffers::{
+    DepthBuffer, DynamicUniformBuffer, Framebuffer, IndexBuffer, IndexType, StorageBuffer,
+    UniformBuffer, VertexBuffer,
+};
+use crate::error::{self, Error};
+use crate::mipmap::MipmapPipeline;
 use crate::pipeline::{Blending, Pipeline, PipelineLayout, Set};
-use crate::sampler::Sampler;
+use crate::report::AdapterReport;
+use crate::sampler::{Sampler, SamplerDescriptor};
 use crate::shader::Shader;
-use crate::texture::Texture;
+use crate::surface::{SurfaceOptions, SurfaceTarget};
+use crate::texture::{Texture, TextureDescriptor};
 use crate::transform::ScreenSpace;
 use crate::vertex::VertexLayout;
 
+type DeviceLostCallback = Box<dyn FnMut(Error) + Send>;
+
 #[derive(Debug)]
 pub struct Device {
+    pub adapter: wgpu::Adapter,
     pub wgpu: wgpu::Device,
     pub queue: wgpu::Queue,
-    pub surface: Option<wgpu::Surface>,
-    size: Size<u32, Pixels>,
+    /// The device's primary surface.
+    pub surface: Option<SurfaceTarget>,
+    optional_features: wgpu::Features,
+    device_lost: DeviceLostHandler,
+    mipmap_pipelines: Mutex<HashMap<TextureFormat, Arc<MipmapPipeline>>>,
+    samplers: Mutex<HashMap<SamplerDescriptor, Arc<wgpu::Sampler>>>,
+}
+
+/// Invokes the device-lost callback from wgpu's uncaptured error handler.
+#[derive(Clone, Default)]
+struct DeviceLostHandler(Arc<Mutex<Option<DeviceLostCallback>>>);
+
+impl DeviceLostHandler {
+    fn handle(&self, err: wgpu::Error) {
+        if Error::is_device_lost(&err) {
+            // The callback is invoked without holding the lock, so that it can
+            // call `Device::on_device_lost`.
+            let callback = self.0.lock().unwrap().take();
+            if let Some(mut callback) = callback {
+                callback(Error::from(err));
+                let mut slot = self.0.lock().unwrap();
+                if slot.is_none() {
+                    *slot = Some(callback);
+                }
+                return;
+            }
+        }
+
+        // Keep wgpu's default behavior for all other uncaptured errors.
+        panic!("wgpu error: {}", error::display_with_sources(&err));
+    }
+}
+
+impl std::fmt::Debug for DeviceLostHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DeviceLostHandler").finish()
+    }
+}
+
+/// Options used when requesting a [`Device`] from a [`wgpu::Adapter`].
+#[derive(Debug, Clone)]
+pub struct DeviceOptions {
+    /// Features the device must support. Device creation fails with
+    /// [`Error::UnsupportedFeatures`] if the adapter lacks any of them.
+    pub required_features: wgpu::Features,
+    /// Features that are enabled only if the adapter supports them. Use
+    /// [`Device::granted_optional_features`] to find out which were enabled.
+    pub optional_features: wgpu::Features,
+    /// The limits to request for the device.
+    pub limits: wgpu::Limits,
+    /// A debug label for the device.
+    pub label: Option<String>,
+}
+
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        Self {
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            label: None,
+        }
+    }
+}
+
+impl DeviceOptions {
+    pub fn with_required_features(mut self, features: wgpu::Features) -> Self {
+        self.required_features |= features;
+        self
+    }
+
+    pub fn with_optional_features(mut self, features: wgpu::Features) -> Self {
+        self.optional_features |= features;
+        self
+    }
+
+    /// Enables the BC, ETC2 and ASTC texture compression features that the
+    /// adapter supports. Use [`Device::supports_format`] to check whether a
+    /// compressed format can be used.
+    pub fn with_texture_compression(self) -> Self {
+        self.with_optional_features(
+            wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ETC2
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC,
+        )
+    }
+
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
 }
 
 impl Device {
     pub async fn for_surface(
         surface: wgpu::Surface,
-        adapter: &wgpu::Adapter,
-    ) -> Result<Self, wgpu::RequestDeviceError> {
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                    label: None,
-                },
-                None,
-            )
-            .await?;
+        adapter: wgpu::Adapter,
+        options: &DeviceOptions,
+    ) -> Result<Self, Error> {
+        Self::request(Some(surface), adapter, options).await
+    }
 
-        Ok(Self {
-            wgpu: device,
-            queue,
-            surface: Some(surface),
-            size: Size::default(),
-        })
+    pub async fn offscreen(adapter: wgpu::Adapter, options: &DeviceOptions) -> Result<Self, Error> {
+        Self::request(None, adapter, options).await
     }
 
-    pub async fn offscreen(adapter: &wgpu::Adapter) -> Result<Self, wgpu::RequestDeviceError> {
+    async fn request(
+        surface: Option<wgpu::Surface>,
+        adapter: wgpu::Adapter,
+        options: &DeviceOptions,
+    ) -> Result<Self, Error> {
+        let supported = adapter.features();
+        let missing = options.required_features - supported;
+        if !missing.is_empty() {
+            return Err(Error::UnsupportedFeatures(missing));
+        }
+        let optional_features = options.optional_features & supported;
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                    label: None,
+                    features: options.required_features | optional_features,
+                    limits: options.limits.clone(),
+                    label: options.label.as_deref(),
                 },
                 None,
             )
             .await?;
 
+        let device_lost = DeviceLostHandler::default();
+        let handler = device_lost.clone();
+        device.on_uncaptured_error(Box::new(move |err| handler.handle(err)));
+
         Ok(Self {
+            adapter,
             wgpu: device,
             queue,
-            surface: None,
-            size: Size::default(),
+            surface: surface.map(SurfaceTarget::new),
+            optional_features,
+            device_lost,
+            mipmap_pipelines: Mutex::default(),
+            samplers: Mutex::default(),
         })
     }
 
+    /// Sets the callback invoked when the device is lost. Without a callback,
+    /// losing the device panics, matching wgpu's default behavior.
+    pub fn on_device_lost<F>(&self, callback: F)
+    where
+        F: FnMut(Error) + Send + 'static,
+    {
+        *self.device_lost.0.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    /// Invokes `create` within wgpu error scopes, returning any validation or
+    /// out-of-memory error it caused instead of passing it to the uncaptured
+    /// error handler, which panics by default.
+    pub async fn capture_errors<T, F>(&self, create: F) -> Result<T, Error>
+    where
+        F: FnOnce(&Self) -> T,
+    {
+        self.wgpu.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        self.wgpu.push_error_scope(wgpu::ErrorFilter::Validation);
+        let value = create(self);
+        let validation = self.wgpu.pop_error_scope();
+        let out_of_memory = self.wgpu.pop_error_scope();
+
+        match (validation.await, out_of_memory.await) {
+            (Some(err), _) | (None, Some(err)) => Err(Error::from(err)),
+            (None, None) => Ok(value),
+        }
+    }
+
+    /// Returns the features enabled on this device.
+    pub fn features(&self) -> wgpu::Features {
+        self.wgpu.features()
+    }
+
+    /// Returns the subset of [`DeviceOptions::optional_features`] that the
+    /// adapter supported and were enabled on this device.
+    pub const fn granted_optional_features(&self) -> wgpu::Features {
+        self.optional_features
+    }
+
+    /// Returns true if the features `format` requires are enabled on this
+    /// device, such as [`wgpu::Features::TEXTURE_COMPRESSION_BC`] for BC
+    /// formats.
+    pub fn supports_format(&self, format: TextureFormat) -> bool {
+        self.features().contains(format.required_features())
+    }
+
+    /// Returns the limits of this device.
+    pub fn limits(&self) -> wgpu::Limits {
+        self.wgpu.limits()
+    }
+
+    /// Returns a report of the adapter and device capabilities.
+    pub fn report(&self) -> AdapterReport {
+        AdapterReport::new(self)
+    }
+
     pub const fn device(&self) -> &wgpu::Device {
         &self.wgpu
     }
 
-    pub const fn size(&self) -> Size<u32, Pixels> {
-        self.size
+    /// Returns the size of the primary surface, or zero if the device has no
+    /// surface.
+    pub fn size(&self) -> Size<u32, Pixels> {
+        self.surface
+            .as_ref()
+            .map(SurfaceTarget::size)
+            .unwrap_or_default()
     }
 
     pub fn device_mut(&mut self) -> &mut wgpu::Device {
@@ -79,7 +254,39 @@ impl Device {
 
     pub fn create_command_encoder(&self) -> wgpu::CommandEncoder {
         self.wgpu
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None })
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("easygpu command encoder"),
+            })
+    }
+
+    /// Creates a [`SurfaceTarget`] for `surface`, which can be rendered to
+    /// using this device. The target must be configured before it can be
+    /// rendered to.
+    ///
+    /// Returns [`Error::IncompatibleSurface`] if this device's adapter can't
+    /// present to `surface`.
+    pub fn create_surface_target(&self, surface: wgpu::Surface) -> Result<SurfaceTarget, Error> {
+        if self.adapter.is_surface_supported(&surface) {
+            Ok(SurfaceTarget::new(surface))
+        } else {
+            Err(Error::IncompatibleSurface)
+        }
+    }
+
+    /// Attaches `surface` to this device as its primary surface, returning
+    /// the surface it replaces. The surface must be configured before it can
+    /// be rendered to.
+    ///
+    /// Returns [`Error::IncompatibleSurface`] if this device's adapter can't
+    /// present to `surface`.
+    pub fn set_surface(&mut self, surface: wgpu::Surface) -> Result<Option<SurfaceTarget>, Error> {
+        let target = self.create_surface_target(surface)?;
+        Ok(self.surface.replace(target))
+    }
+
+    /// Detaches and returns this device's primary surface.
+    pub fn take_surface(&mut self) -> Option<SurfaceTarget> {
+        self.surface.take()
     }
 
     pub fn configure<PM: Into<wgpu::PresentMode>>(
@@ -87,56 +294,117 @@ impl Device {
         size: Size<u32, ScreenSpace>,
         mode: PM,
         format: TextureFormat,
-    ) {
-        let desc = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            present_mode: mode.into(),
-            width: size.width,
-            height: size.height,
-            alpha_mode: CompositeAlphaMode::Auto,
-            view_formats: Vec::new(),
-        };
+    ) -> Result<(), Error> {
+        self.configure_with_options(
+            size,
+            &SurfaceOptions::new(format).with_present_mode(mode.into()),
+        )
+    }
+
+    /// Configures the primary surface.
+    pub fn configure_with_options(
+        &mut self,
+        size: Size<u32, ScreenSpace>,
+        options: &SurfaceOptions,
+    ) -> Result<(), Error> {
+        let surface = self.surface.as_mut().ok_or(Error::NoSurface)?;
+        surface.configure(&self.wgpu, size, options);
+        Ok(())
+    }
+
+    /// Configures the primary surface again using the configuration it was
+    /// last configured with. Returns false if the surface has not been
+    /// configured.
+    pub fn reconfigure(&self) -> bool {
+        self.surface
+            .as_ref()
+            .is_some_and(|surface| surface.reconfigure(&self.wgpu))
+    }
+
+    /// Returns the configuration the primary surface was last configured with.
+    pub fn surface_configuration(&self) -> Option<&wgpu::SurfaceConfiguration> {
+        self.surface.as_ref()?.configuration()
+    }
+
+    /// Returns the capabilities of the surface when used with this device's
+    /// adapter, or `None` if this device has no surface.
+    pub fn surface_capabilities(&self) -> Option<wgpu::SurfaceCapabilities> {
         self.surface
             .as_ref()
-            .expect("create_swap_chain only works when initalized with a wgpu::Surface")
-            .configure(&self.wgpu, &desc);
-        self.size = size;
+            .map(|surface| surface.capabilities(&self.adapter))
     }
 
-    pub fn create_pipeline_layout(&self, ss: &[Set]) -> PipelineLayout {
+    /// Returns the [`SurfaceOptions`] using the first of `formats` and
+    /// `present_modes` that the surface supports. See
+    /// [`SurfaceOptions::negotiate`] for details.
+    pub fn negotiate_surface_options(
+        &self,
+        formats: &[TextureFormat],
+        present_modes: &[wgpu::PresentMode],
+    ) -> Option<SurfaceOptions> {
+        let capabilities = self.surface_capabilities()?;
+        SurfaceOptions::negotiate(&capabilities, formats, present_modes)
+    }
+
+    pub fn create_pipeline_layout(&self, ss: &[Set], label: Option<&str>) -> PipelineLayout {
+        let label = label_or(label, || String::from("easygpu pipeline"));
         let mut sets = Vec::new();
         for (i, s) in ss.iter().enumerate() {
-            sets.push(self.create_binding_group_layout(i as u32, s.0))
+            sets.push(self.create_binding_group_layout(
+                i as u32,
+                s.0,
+                Some(&format!("{} set {}", label, i)),
+            ))
         }
         PipelineLayout { sets }
     }
 
-    pub fn create_shader(&self, source: &[u8]) -> Shader {
+    pub fn create_shader(&self, source: &[u8], label: Option<&str>) -> Shader {
         Shader {
             wgpu: self
                 .wgpu
                 .create_shader_module(wgpu::ShaderModuleDescriptor {
                     source: wgpu::util::make_spirv(source),
-                    label: None, // TODO labels would be nice
+                    label: Some(&label_or(label, || String::from("easygpu shader"))),
                 }),
         }
     }
 
-    pub fn create_shader_from_wgsl(&self, source: &str) -> Shader {
+    /// Creates a shader from SPIR-V, returning an error if it fails to compile.
+    pub async fn try_create_shader(
+        &self,
+        source: &[u8],
+        label: Option<&str>,
+    ) -> Result<Shader, Error> {
+        self.capture_errors(|device| device.create_shader(source, label))
+            .await
+            .map_err(Error::into_shader_error)
+    }
+
+    /// Creates a shader from WGSL, returning an error if it fails to compile.
+    pub async fn try_create_shader_from_wgsl(
+        &self,
+        source: &str,
+        label: Option<&str>,
+    ) -> Result<Shader, Error> {
+        self.capture_errors(|device| device.create_shader_from_wgsl(source, label))
+            .await
+            .map_err(Error::into_shader_error)
+    }
+
+    pub fn create_shader_from_wgsl(&self, source: &str, label: Option<&str>) -> Shader {
         Shader {
             wgpu: self
                 .wgpu
                 .create_shader_module(wgpu::ShaderModuleDescriptor {
                     source: wgpu::ShaderSource::Wgsl(source.into()),
-                    label: None, // TODO labels would be nice
+                    label: Some(&label_or(label, || String::from("easygpu shader"))),
                 }),
         }
     }
 
     pub fn create_encoder(&self) -> wgpu::CommandEncoder {
-        self.wgpu
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None })
+        self.create_command_encoder()
     }
 
     pub fn create_texture(
@@ -145,39 +413,95 @@ impl Device {
         format: TextureFormat,
         usage: TextureUsages,
         sample_count: u32,
+        label: Option<&str>,
+    ) -> Texture {
+        self.create_texture_with_mip_levels(size, format, usage, sample_count, 1, label)
+    }
+
+    /// Creates a texture with `mip_level_count` mip levels. Use
+    /// [`Texture::full_mip_level_count`] for a full mip chain.
+    pub fn create_texture_with_mip_levels(
+        &self,
+        size: Size<u32, ScreenSpace>,
+        format: TextureFormat,
+        usage: TextureUsages,
+        sample_count: u32,
+        mip_level_count: u32,
+        label: Option<&str>,
     ) -> Texture {
+        let mut desc = TextureDescriptor::new(size, format, usage)
+            .with_mip_level_count(mip_level_count)
+            .with_sample_count(sample_count);
+        desc.label = label;
+        self.create_texture_with_descriptor(&desc)
+    }
+
+    /// Creates a texture described by `desc`, which may be an array, a cube
+    /// map or a 3D texture. Mip levels of 2D textures with a single layer can
+    /// be generated with [`Texture::generate_mipmaps`].
+    pub fn create_texture_with_descriptor(&self, desc: &TextureDescriptor<'_>) -> Texture {
+        let size = desc.size;
+        let label = label_or(desc.label, || {
+            format!(
+                "easygpu texture {}x{}x{}",
+                size.width, size.height, desc.layers
+            )
+        });
         let texture_extent = wgpu::Extent3d {
             width: size.width,
             height: size.height,
-            depth_or_array_layers: 1,
+            depth_or_array_layers: desc.layers,
         };
         let texture = self.wgpu.create_texture(&wgpu::TextureDescriptor {
             size: texture_extent,
-            mip_level_count: 1,
-            sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format,
-            usage,
-            label: None,
+            mip_level_count: desc.mip_level_count,
+            sample_count: desc.sample_count,
+            dimension: desc.dimension(),
+            format: desc.format,
+            usage: desc.usage,
+            label: Some(&label),
             view_formats: &[],
         });
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(desc.view_dimension),
+            ..Default::default()
+        });
         Texture {
             wgpu: texture,
             view: texture_view,
             extent: texture_extent,
-            format,
+            format: desc.format,
             size,
+            view_dimension: desc.view_dimension,
+            label: label.into_owned(),
+            mipmaps: OnceLock::new(),
         }
     }
 
+    pub async fn try_create_texture(
+        &self,
+        size: Size<u32, ScreenSpace>,
+        format: TextureFormat,
+        usage: TextureUsages,
+        sample_count: u32,
+        label: Option<&str>,
+    ) -> Result<Texture, Error> {
+        self.capture_errors(|device| {
+            device.create_texture(size, format, usage, sample_count, label)
+        })
+        .await
+    }
+
     pub fn create_framebuffer(
         &self,
         size: Size<u32, ScreenSpace>,
         format: TextureFormat,
         sample_count: u32,
+        label: Option<&str>,
     ) -> Framebuffer {
+        let label = label_or(label, || {
+            format!("easygpu framebuffer {}x{}", size.width, size.height)
+        });
         let extent = wgpu::Extent3d {
             width: size.width,
             height: size.height,
@@ -186,17 +510,26 @@ impl Device {
         let texture = self.wgpu.create_texture(&wgpu::TextureDescriptor {
             size: extent,
             mip_level_count: 1,
-            sample_count,
+            sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
             usage: TextureUsages::TEXTURE_BINDING
                 | TextureUsages::COPY_DST
                 | TextureUsages::COPY_SRC
                 | TextureUsages::RENDER_ATTACHMENT,
-            label: None,
+            label: Some(&label),
             view_formats: &[],
         });
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let multisample = (sample_count > 1).then(|| {
+            self.create_texture(
+                size,
+                format,
+                TextureUsages::RENDER_ATTACHMENT,
+                sample_count,
+                Some(&format!("{} multisample", label)),
+            )
+        });
 
         Framebuffer {
             texture: Texture {
@@ -205,12 +538,35 @@ impl Device {
                 extent,
                 format,
                 size,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                label: label.to_string(),
+                mipmaps: OnceLock::new(),
             },
-            depth: self.create_zbuffer(size, sample_count),
+            multisample,
+            depth: self.create_zbuffer(size, sample_count, Some(&format!("{} depth", label))),
         }
     }
 
-    pub fn create_zbuffer(&self, size: Size<u32, ScreenSpace>, sample_count: u32) -> DepthBuffer {
+    pub async fn try_create_framebuffer(
+        &self,
+        size: Size<u32, ScreenSpace>,
+        format: TextureFormat,
+        sample_count: u32,
+        label: Option<&str>,
+    ) -> Result<Framebuffer, Error> {
+        self.capture_errors(|device| device.create_framebuffer(size, format, sample_count, label))
+            .await
+    }
+
+    pub fn create_zbuffer(
+        &self,
+        size: Size<u32, ScreenSpace>,
+        sample_count: u32,
+        label: Option<&str>,
+    ) -> DepthBuffer {
+        let label = label_or(label, || {
+            format!("easygpu depth buffer {}x{}", size.width, size.height)
+        });
         let format = DepthBuffer::FORMAT;
         let extent = wgpu::Extent3d {
             width: size.width,
@@ -219,12 +575,12 @@ impl Device {
         };
         let wgpu = self.wgpu.create_texture(&wgpu::TextureDescriptor {
             size: extent,
-            label: None,
+            label: Some(&label),
             mip_level_count: 1,
             sample_count,
             dimension: wgpu::TextureDimension::D2,
             format,
-            usage: TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT,
+            usage: DepthBuffer::USAGE,
             view_formats: &[],
         });
         let view = wgpu.create_view(&wgpu::TextureViewDescriptor::default());
@@ -236,6 +592,9 @@ impl Device {
                 extent,
                 format,
                 size,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                label: label.into_owned(),
+                mipmaps: OnceLock::new(),
             },
         }
     }
@@ -244,7 +603,11 @@ impl Device {
         &self,
         layout: &BindingGroupLayout,
         binds: &[&dyn Bind],
+        label: Option<&str>,
     ) -> BindingGroup {
+        let label = label_or(label, || {
+            format!("easygpu binding group {}", layout.set_index)
+        });
         assert_eq!(
             binds.len(),
             layout.size,
@@ -261,23 +624,51 @@ impl Device {
             layout.set_index,
             self.wgpu.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &layout.wgpu,
-                label: None,
+                label: Some(&label),
                 entries: bindings.as_slice(),
             }),
         )
     }
 
-    pub fn create_buffer<T: bytemuck::Pod>(&self, vertices: &[T]) -> VertexBuffer
+    pub async fn try_create_binding_group(
+        &self,
+        layout: &BindingGroupLayout,
+        binds: &[&dyn Bind],
+        label: Option<&str>,
+    ) -> Result<BindingGroup, Error> {
+        self.capture_errors(|device| device.create_binding_group(layout, binds, label))
+            .await
+    }
+
+    pub fn create_buffer<T>(&self, vertices: &[T], label: Option<&str>) -> VertexBuffer<T>
     where
-        T: 'static + Copy,
+        T: bytemuck::Pod + 'static + Copy,
     {
-        VertexBuffer {
-            wgpu: self.create_buffer_from_slice(vertices, wgpu::BufferUsages::VERTEX),
-            size: (vertices.len() * std::mem::size_of::<T>()) as u32,
-        }
+        VertexBuffer::new(
+            self.create_buffer_from_slice(
+                vertices,
+                wgpu::BufferUsages::VERTEX
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                Some(&label_or(label, || String::from("easygpu vertex buffer"))),
+            ),
+            vertices.len() as u32,
+        )
     }
 
-    pub fn create_uniform_buffer<T>(&self, buf: &[T]) -> UniformBuffer
+    pub async fn try_create_buffer<T>(
+        &self,
+        vertices: &[T],
+        label: Option<&str>,
+    ) -> Result<VertexBuffer<T>, Error>
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        self.capture_errors(|device| device.create_buffer(vertices, label))
+            .await
+    }
+
+    pub fn create_uniform_buffer<T>(&self, buf: &[T], label: Option<&str>) -> UniformBuffer
     where
         T: bytemuck::Pod + 'static + Copy,
     {
@@ -287,55 +678,135 @@ impl Device {
             wgpu: self
                 .wgpu
                 .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Uniform Buffer"),
+                    label: Some(&label_or(label, || String::from("easygpu uniform buffer"))),
                     contents: bytemuck::cast_slice(buf),
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    usage: wgpu::BufferUsages::UNIFORM
+                        | wgpu::BufferUsages::COPY_DST
+                        | wgpu::BufferUsages::COPY_SRC,
                 }),
         }
     }
 
-    pub fn create_index(&self, indices: &[u16]) -> IndexBuffer {
-        let index_buf = self.create_buffer_from_slice(indices, wgpu::BufferUsages::INDEX);
-        IndexBuffer {
-            wgpu: index_buf,
-            elements: indices.len() as u32,
+    pub async fn try_create_uniform_buffer<T>(
+        &self,
+        buf: &[T],
+        label: Option<&str>,
+    ) -> Result<UniformBuffer, Error>
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        self.capture_errors(|device| device.create_uniform_buffer(buf, label))
+            .await
+    }
+
+    /// Creates a uniform buffer containing each value of `buf` at an offset
+    /// aligned to `min_uniform_buffer_offset_alignment`, so that each value
+    /// can be bound with a dynamic offset.
+    pub fn create_dynamic_uniform_buffer<T>(
+        &self,
+        buf: &[T],
+        label: Option<&str>,
+    ) -> DynamicUniformBuffer<T>
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        let stride =
+            DynamicUniformBuffer::<T>::stride(self.limits().min_uniform_buffer_offset_alignment);
+        DynamicUniformBuffer::new(
+            self.create_buffer_from_slice(
+                &DynamicUniformBuffer::pack(buf, stride),
+                wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                Some(&label_or(label, || {
+                    String::from("easygpu dynamic uniform buffer")
+                })),
+            ),
+            stride,
+            buf.len(),
+        )
+    }
+
+    /// Creates a storage buffer containing `buf`, which shaders can read and
+    /// write.
+    pub fn create_storage_buffer<T>(&self, buf: &[T], label: Option<&str>) -> StorageBuffer
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        StorageBuffer {
+            size: std::mem::size_of::<T>(),
+            count: buf.len(),
+            wgpu: self.create_buffer_from_slice(
+                buf,
+                wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                Some(&label_or(label, || String::from("easygpu storage buffer"))),
+            ),
         }
     }
 
-    pub fn create_sampler(&self, min_filter: FilterMode, mag_filter: FilterMode) -> Sampler {
+    pub fn create_index<I: IndexType>(&self, indices: &[I], label: Option<&str>) -> IndexBuffer<I> {
+        let index_buf = self.create_buffer_from_slice(
+            indices,
+            wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            Some(&label_or(label, || String::from("easygpu index buffer"))),
+        );
+        IndexBuffer::new(index_buf, indices.len() as u32)
+    }
+
+    pub fn create_sampler(
+        &self,
+        min_filter: FilterMode,
+        mag_filter: FilterMode,
+        label: Option<&str>,
+    ) -> Sampler {
+        let descriptor = SamplerDescriptor::new(min_filter).with_mag_filter(mag_filter);
+        let label = label_or(label, || String::from("easygpu sampler"));
         Sampler {
-            wgpu: self.wgpu.create_sampler(&wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter,
-                min_filter,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                lod_min_clamp: 0.,
-                lod_max_clamp: 100.0,
-                compare: None,
-                anisotropy_clamp: 1,
-                label: None,
-                border_color: None,
-            }),
+            wgpu: Arc::new(self.wgpu.create_sampler(&descriptor.to_wgpu(Some(&label)))),
+            descriptor,
         }
     }
 
-    pub fn create_binding_group_layout(&self, index: u32, slots: &[Binding]) -> BindingGroupLayout {
+    /// Returns a sampler described by `descriptor`. Samplers are cached, so
+    /// equal descriptors share one [`wgpu::Sampler`].
+    pub fn create_sampler_with_descriptor(&self, descriptor: &SamplerDescriptor) -> Sampler {
+        let wgpu = self
+            .samplers
+            .lock()
+            .unwrap()
+            .entry(*descriptor)
+            .or_insert_with(|| {
+                Arc::new(
+                    self.wgpu
+                        .create_sampler(&descriptor.to_wgpu(Some("easygpu cached sampler"))),
+                )
+            })
+            .clone();
+        Sampler {
+            wgpu,
+            descriptor: *descriptor,
+        }
+    }
+
+    pub fn create_binding_group_layout(
+        &self,
+        index: u32,
+        slots: &[Binding],
+        label: Option<&str>,
+    ) -> BindingGroupLayout {
         let mut bindings = Vec::new();
 
         for s in slots {
-            bindings.push(wgpu::BindGroupLayoutEntry {
-                binding: bindings.len() as u32,
-                visibility: s.stage,
-                ty: s.binding.to_wgpu(),
-                count: None,
-            });
+            bindings.push(s.to_wgpu(bindings.len() as u32));
         }
         let layout = self
             .wgpu
             .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: None,
+                label: Some(&label_or(label, || {
+                    format!("easygpu binding group layout {}", index)
+                })),
                 entries: bindings.as_slice(),
             });
         BindingGroupLayout::new(index, layout, bindings.len())
@@ -345,10 +816,11 @@ impl Device {
         &self,
         slice: &[T],
         usage: wgpu::BufferUsages,
+        label: Option<&str>,
     ) -> wgpu::Buffer {
         self.wgpu
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
+                label: Some(&label_or(label, || String::from("easygpu buffer"))),
                 contents: bytemuck::cast_slice(slice),
                 usage,
             })
@@ -363,6 +835,55 @@ impl Device {
             .write_buffer(&buf.wgpu, 0, bytemuck::cast_slice(slice));
     }
 
+    /// Replaces the values of `buf`, starting with the first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice` contains more values than `buf`.
+    pub fn update_dynamic_uniform_buffer<T: bytemuck::Pod + Copy + 'static>(
+        &self,
+        slice: &[T],
+        buf: &DynamicUniformBuffer<T>,
+    ) {
+        assert!(
+            slice.len() <= buf.count,
+            "{} values don't fit in a dynamic uniform buffer of {}",
+            slice.len(),
+            buf.count
+        );
+        if !slice.is_empty() {
+            self.queue
+                .write_buffer(&buf.wgpu, 0, &DynamicUniformBuffer::pack(slice, buf.stride));
+        }
+    }
+
+    pub fn update_storage_buffer<T: bytemuck::Pod + Copy + 'static>(
+        &self,
+        slice: &[T],
+        buf: &StorageBuffer,
+    ) {
+        self.queue
+            .write_buffer(&buf.wgpu, 0, bytemuck::cast_slice(slice));
+    }
+
+    /// Returns the pipeline used to generate mip levels of textures of
+    /// `format`, creating it on first use.
+    pub(crate) fn mipmap_pipeline(&self, format: TextureFormat) -> Arc<MipmapPipeline> {
+        self.mipmap_pipelines
+            .lock()
+            .unwrap()
+            .entry(format)
+            .or_insert_with(|| Arc::new(MipmapPipeline::new(self, format)))
+            .clone()
+    }
+
+    /// Processes completed work, such as invoking the callbacks of buffers
+    /// that finished mapping, without blocking. Returns true if the queue is
+    /// empty.
+    pub fn poll(&self) -> bool {
+        self.wgpu.poll(wgpu::Maintain::Poll)
+    }
+
     pub fn submit<I: IntoIterator<Item = wgpu::CommandBuffer>>(
         &mut self,
         cmds: I,
@@ -370,6 +891,33 @@ impl Device {
         self.queue.submit(cmds)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub async fn try_create_pipeline(
+        &self,
+        pipeline_layout: PipelineLayout,
+        vertex_layout: VertexLayout,
+        blending: Blending,
+        vs: &Shader,
+        fs: &Shader,
+        swapchain_format: TextureFormat,
+        multisample: MultisampleState,
+        label: Option<&str>,
+    ) -> Result<Pipeline, Error> {
+        self.capture_errors(|device| {
+            device.create_pipeline(
+                pipeline_layout,
+                vertex_layout,
+                blending,
+                vs,
+                fs,
+                swapchain_format,
+                multisample,
+                label,
+            )
+        })
+        .await
+    }
+
     // TODO clippy::too_many_arguments
     #[allow(clippy::too_many_arguments)]
     pub fn create_pipeline(
@@ -381,7 +929,9 @@ impl Device {
         fs: &Shader,
         swapchain_format: TextureFormat,
         multisample: MultisampleState,
+        label: Option<&str>,
     ) -> Pipeline {
+        let label = label_or(label, || String::from("easygpu pipeline"));
         let vertex_attrs = vertex_layout.to_wgpu();
 
         let mut sets = Vec::new();
@@ -391,7 +941,7 @@ impl Device {
         let layout = &self
             .wgpu
             .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: None,
+                label: Some(&format!("{} layout", label)),
                 bind_group_layouts: sets.as_slice(),
                 push_constant_ranges: &[],
             });
@@ -401,7 +951,7 @@ impl Device {
         let wgpu = self
             .wgpu
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: None,
+                label: Some(&label),
                 layout: Some(layout),
                 vertex: wgpu::VertexState {
                     module: &vs.wgpu,
@@ -465,3 +1015,14 @@ impl Device {
         }
     }
 }
+
+/// Returns `label`, or the label produced by `default` if no label was given.
+pub(crate) fn label_or<'a>(
+    label: Option<&'a str>,
+    default: impl FnOnce() -> String,
+) -> Cow<'a, str> {
+    match label {
+        Some(label) => Cow::Borrowed(label),
+        None => Cow::Owned(default()),
+    }
+}
@@ -1,15 +1,214 @@
 use std::io;
+#[cfg(feature = "image")]
+use std::sync::Arc;
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum Error {
-    #[error("a suitable graphics adapter was not found")]
-    NoAdaptersFound,
+    #[error("a suitable graphics adapter was not found ({} rejected)", rejected.len())]
+    NoAdaptersFound { rejected: Vec<wgpu::AdapterInfo> },
     #[error("device creation error")]
     RequestDeviceError(#[from] wgpu::RequestDeviceError),
+    #[error("the adapter does not support the required features: {0:?}")]
+    UnsupportedFeatures(wgpu::Features),
+    #[error("validation error: {0}")]
+    Validation(String),
+    #[error("out of memory")]
+    OutOfMemory,
+    #[error("shader compilation error: {0}")]
+    ShaderCompilation(String),
+    #[error("the device was lost: {0}")]
+    DeviceLost(String),
+    #[error("the device has no surface")]
+    NoSurface,
+    #[error("the surface has not been configured")]
+    SurfaceNotConfigured,
+    #[error("the adapter does not support the surface")]
+    IncompatibleSurface,
+    #[error("surface error: {0}")]
+    Surface(wgpu::SurfaceError),
+    #[error("the texture must have COPY_SRC usage and a sample count of 1 to be read")]
+    TextureNotReadable,
+    #[error("reading textures with the format {0:?} is not supported")]
+    UnsupportedReadbackFormat(wgpu::TextureFormat),
+    #[error("the region to read is outside of the texture or not aligned to its blocks")]
+    InvalidReadbackRegion,
+    #[error("the buffer must have COPY_SRC usage to be read")]
+    BufferNotReadable,
+    #[error("the range to read is outside of the buffer or not a multiple of the element size")]
+    InvalidBufferRange,
+    #[error(
+        "can't write {len} bytes at offset {offset}: writes must start at a 4 byte aligned \
+         offset within the buffer's contents, and only writes that reach its end may have \
+         an unaligned length"
+    )]
+    InvalidBufferWrite { offset: u64, len: u64 },
+    #[error("uploading texels to textures with the format {0:?} is not supported")]
+    UnsupportedUploadFormat(wgpu::TextureFormat),
+    #[error("texels of {format:?} are {expected} bytes, but the texel type is {found} bytes")]
+    TexelSizeMismatch {
+        format: wgpu::TextureFormat,
+        expected: u32,
+        found: usize,
+    },
+    #[error("expected {expected} texels, but only {found} were provided")]
+    NotEnoughTexels { expected: usize, found: usize },
+    #[error("the region to transfer to is outside of the texture")]
+    InvalidTransferRegion,
+    #[error("the device doesn't support {0:?}, and it can't be decompressed on the CPU")]
+    UnsupportedTextureFormat(wgpu::TextureFormat),
+    #[error("invalid texture container: {0}")]
+    TextureContainer(String),
+    #[error("the texture's mip levels can't be generated on the GPU")]
+    MipmapsUnsupported,
+    #[error("buffer mapping error: {0}")]
+    BufferMap(#[from] wgpu::BufferAsyncError),
+    #[cfg(feature = "image")]
+    #[error("converting textures with the format {0:?} to images is not supported")]
+    UnsupportedImageFormat(wgpu::TextureFormat),
+    #[cfg(feature = "image")]
+    #[error("image error: {0}")]
+    Image(Arc<image::ImageError>),
+}
+
+impl Error {
+    /// Reports validation errors raised while creating a shader module as
+    /// shader compilation errors.
+    pub(crate) fn into_shader_error(self) -> Self {
+        match self {
+            Self::Validation(message) => Self::ShaderCompilation(message),
+            other => other,
+        }
+    }
+
+    /// Returns true if `error`, or any of its sources, is wgpu-core's
+    /// `DeviceError::Lost`.
+    pub(crate) fn is_device_lost(error: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = Some(error);
+        while let Some(error) = source {
+            if is_lost_device_error(error) {
+                return true;
+            }
+            source = error.source();
+        }
+        false
+    }
+}
+
+/// Returns true if `error` is or wraps `DeviceError::Lost`. Most wgpu-core
+/// errors wrap `DeviceError` transparently, which hides it from the source
+/// chain, so each public error type with a `DeviceError` variant is checked.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_lost_device_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    use wgpu_core::binding_model::{
+        CreateBindGroupError, CreateBindGroupLayoutError, CreatePipelineLayoutError,
+    };
+    use wgpu_core::device::queue::{QueueSubmitError, QueueWriteError};
+    use wgpu_core::device::DeviceError;
+    use wgpu_core::pipeline::{
+        CreateComputePipelineError, CreateRenderPipelineError, CreateShaderModuleError,
+    };
+    use wgpu_core::present::{ConfigureSurfaceError, SurfaceError};
+    use wgpu_core::resource::{
+        BufferAccessError, CreateBufferError, CreateQuerySetError, CreateSamplerError,
+        CreateTextureError,
+    };
+
+    macro_rules! is_lost {
+        ($($error:ident::$variant:ident),+ $(,)?) => {
+            $(matches!(
+                error.downcast_ref::<$error>(),
+                Some($error::$variant(DeviceError::Lost))
+            ))||+
+        };
+    }
+
+    matches!(error.downcast_ref::<DeviceError>(), Some(DeviceError::Lost))
+        || is_lost!(
+            CreateBindGroupError::Device,
+            CreateBindGroupLayoutError::Device,
+            CreatePipelineLayoutError::Device,
+            QueueSubmitError::Queue,
+            QueueWriteError::Queue,
+            CreateComputePipelineError::Device,
+            CreateRenderPipelineError::Device,
+            CreateShaderModuleError::Device,
+            ConfigureSurfaceError::Device,
+            SurfaceError::Device,
+            BufferAccessError::Device,
+            CreateBufferError::Device,
+            CreateQuerySetError::Device,
+            CreateSamplerError::Device,
+            CreateTextureError::Device,
+        )
+}
+
+/// The WebGPU backend doesn't report wgpu-core errors.
+#[cfg(target_arch = "wasm32")]
+fn is_lost_device_error(_error: &(dyn std::error::Error + 'static)) -> bool {
+    false
+}
+
+/// Formats `error` followed by each of its sources.
+pub(crate) fn display_with_sources(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(&format!("\n  caused by: {}", error));
+        source = error.source();
+    }
+    message
+}
+
+impl From<wgpu::Error> for Error {
+    fn from(err: wgpu::Error) -> Self {
+        match &err {
+            wgpu::Error::OutOfMemory { .. } => Self::OutOfMemory,
+            wgpu::Error::Validation { description, .. } => {
+                if Self::is_device_lost(&err) {
+                    Self::DeviceLost(description.clone())
+                } else {
+                    Self::Validation(description.clone())
+                }
+            }
+        }
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(err: wgpu::SurfaceError) -> Self {
+        match err {
+            wgpu::SurfaceError::OutOfMemory => Self::OutOfMemory,
+            other => Self::Surface(other),
+        }
+    }
 }
 
 impl From<Error> for io::Error {
     fn from(err: Error) -> io::Error {
-        io::Error::new(io::ErrorKind::Other, err)
+        io::Error::other(err)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use wgpu_core::device::queue::QueueSubmitError;
+    use wgpu_core::device::DeviceError;
+    use wgpu_core::resource::CreateBufferError;
+
+    use super::Error;
+
+    #[test]
+    fn detects_lost_devices() {
+        assert!(Error::is_device_lost(&DeviceError::Lost));
+        assert!(Error::is_device_lost(&QueueSubmitError::Queue(
+            DeviceError::Lost
+        )));
+        assert!(Error::is_device_lost(&CreateBufferError::Device(
+            DeviceError::Lost
+        )));
+        assert!(!Error::is_device_lost(&DeviceError::OutOfMemory));
+        assert!(!Error::is_device_lost(&CreateBufferError::Device(
+            DeviceError::Invalid
+        )));
     }
 }
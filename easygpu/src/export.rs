@@ -0,0 +1,375 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use image::RgbaImage;
+use wgpu::{CompositeAlphaMode, TextureFormat};
+
+use crate::buffers::Framebuffer;
+use crate::error::Error;
+use crate::readback::TextureData;
+use crate::renderer::{RenderFrame, Renderer};
+use crate::texture::Texture;
+
+/// How the alpha channel of texels is interpreted when converting them to an
+/// image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageAlpha {
+    /// The color channels are independent of the alpha channel.
+    Straight,
+    /// The color channels have been multiplied by the alpha channel, and are
+    /// divided by it during the conversion.
+    Premultiplied,
+    /// The alpha channel is ignored and the image is fully opaque.
+    Opaque,
+}
+
+impl ImageAlpha {
+    /// Returns how the texels of a surface configured with `mode` are
+    /// interpreted.
+    pub fn from_composite_alpha_mode(mode: CompositeAlphaMode) -> Self {
+        match mode {
+            CompositeAlphaMode::PreMultiplied => Self::Premultiplied,
+            CompositeAlphaMode::PostMultiplied | CompositeAlphaMode::Inherit => Self::Straight,
+            CompositeAlphaMode::Auto | CompositeAlphaMode::Opaque => Self::Opaque,
+        }
+    }
+}
+
+/// How the color channels of a format are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    /// sRGB encoded, and decoded by the GPU when sampled.
+    Srgb,
+    /// Stored as-is, which is assumed to already be suitable for display.
+    Unorm,
+    /// Linear floating point values, which are sRGB encoded during the
+    /// conversion.
+    Linear,
+}
+
+impl TextureData {
+    /// Converts the texels to an 8-bit sRGB image, swizzling, unpremultiplying
+    /// and encoding the texels as needed based on their format.
+    ///
+    /// Supported formats are `R8Unorm`, `Rg8Unorm`, `Rgba8Unorm`,
+    /// `Bgra8Unorm`, their sRGB variants, `Rgb10a2Unorm`, `R16Float`,
+    /// `Rgba16Float`, `R32Float`, `Rgba32Float` and `Depth32Float`.
+    /// Single-channel formats are converted to grayscale.
+    pub fn to_rgba_image(&self, alpha: ImageAlpha) -> Result<RgbaImage, Error> {
+        let encoding = match self.format {
+            TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8UnormSrgb => Encoding::Srgb,
+            TextureFormat::Rgba16Float | TextureFormat::Rgba32Float => Encoding::Linear,
+            _ => Encoding::Unorm,
+        };
+        let texel_size = self
+            .format
+            .block_size(Some(self.aspect))
+            .ok_or(Error::UnsupportedImageFormat(self.format))? as usize;
+
+        let mut pixels = Vec::with_capacity(self.bytes.len() / texel_size * 4);
+        for texel in self.bytes.chunks_exact(texel_size) {
+            let mut color =
+                decode(self.format, texel).ok_or(Error::UnsupportedImageFormat(self.format))?;
+            let a = color[3];
+            match alpha {
+                ImageAlpha::Opaque => color[3] = 1.,
+                ImageAlpha::Premultiplied if a > 0. => {
+                    for channel in &mut color[..3] {
+                        *channel = match encoding {
+                            Encoding::Srgb => linear_to_srgb(srgb_to_linear(*channel) / a),
+                            Encoding::Unorm | Encoding::Linear => *channel / a,
+                        };
+                    }
+                }
+                ImageAlpha::Premultiplied | ImageAlpha::Straight => {}
+            }
+            if encoding == Encoding::Linear {
+                for channel in &mut color[..3] {
+                    *channel = linear_to_srgb(*channel);
+                }
+            }
+            pixels.extend(
+                color
+                    .iter()
+                    .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8),
+            );
+        }
+
+        Ok(
+            RgbaImage::from_raw(self.size.width, self.size.height, pixels)
+                .expect("image size matches texel data"),
+        )
+    }
+}
+
+impl Texture {
+    /// Reads the texture's first mip level into an 8-bit sRGB image. See
+    /// [`TextureData::to_rgba_image`] for the supported formats.
+    pub fn to_rgba_image(&self, renderer: &mut Renderer) -> Result<RgbaImage, Error> {
+        renderer
+            .read_texture(self, None, 0)?
+            .to_rgba_image(ImageAlpha::Straight)
+    }
+}
+
+impl Framebuffer {
+    /// Reads the framebuffer into an 8-bit sRGB image.
+    pub fn to_rgba_image(&self, renderer: &mut Renderer) -> Result<RgbaImage, Error> {
+        self.texture.to_rgba_image(renderer)
+    }
+
+    /// Saves the framebuffer as a PNG image at `path`.
+    pub fn save_png(&self, renderer: &mut Renderer, path: impl AsRef<Path>) -> Result<(), Error> {
+        save_png(&self.to_rgba_image(renderer)?, path.as_ref())
+    }
+}
+
+impl RenderFrame {
+    /// Reads the frame into an 8-bit sRGB image, using the surface's alpha
+    /// mode to interpret the alpha channel.
+    ///
+    /// The surface must have been configured with
+    /// [`wgpu::TextureUsages::COPY_SRC`] using
+    /// [`SurfaceOptions::with_usage`](crate::surface::SurfaceOptions::with_usage),
+    /// and the frame must have been rendered to using [`Renderer::present`]
+    /// before calling this function.
+    pub fn to_rgba_image(&self, renderer: &mut Renderer) -> Result<RgbaImage, Error> {
+        let surface_texture = self.wgpu.as_ref().ok_or(Error::TextureNotReadable)?;
+        renderer
+            .read_wgpu_texture(&surface_texture.texture, None, 0)?
+            .to_rgba_image(ImageAlpha::from_composite_alpha_mode(self.alpha_mode))
+    }
+
+    /// Saves the frame as a PNG image at `path`. See
+    /// [`RenderFrame::to_rgba_image`] for the requirements.
+    pub fn save_png(&self, renderer: &mut Renderer, path: impl AsRef<Path>) -> Result<(), Error> {
+        save_png(&self.to_rgba_image(renderer)?, path.as_ref())
+    }
+}
+
+fn save_png(image: &RgbaImage, path: &Path) -> Result<(), Error> {
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|err| Error::Image(Arc::new(err)))
+}
+
+/// Decodes a texel of `format` into RGBA channels, or returns `None` if the
+/// format is not supported.
+fn decode(format: TextureFormat, texel: &[u8]) -> Option<[f32; 4]> {
+    let unorm8 = |value: u8| f32::from(value) / 255.;
+    let f16 = |index: usize| f16_to_f32(u16::from_le_bytes([texel[index], texel[index + 1]]));
+    let f32 = |index: usize| {
+        f32::from_le_bytes([
+            texel[index],
+            texel[index + 1],
+            texel[index + 2],
+            texel[index + 3],
+        ])
+    };
+
+    let color = match format {
+        TextureFormat::R8Unorm => {
+            let value = unorm8(texel[0]);
+            [value, value, value, 1.]
+        }
+        TextureFormat::Rg8Unorm => [unorm8(texel[0]), unorm8(texel[1]), 0., 1.],
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => [
+            unorm8(texel[0]),
+            unorm8(texel[1]),
+            unorm8(texel[2]),
+            unorm8(texel[3]),
+        ],
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => [
+            unorm8(texel[2]),
+            unorm8(texel[1]),
+            unorm8(texel[0]),
+            unorm8(texel[3]),
+        ],
+        TextureFormat::Rgb10a2Unorm => {
+            let packed = u32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]);
+            [
+                (packed & 0x3ff) as f32 / 1023.,
+                ((packed >> 10) & 0x3ff) as f32 / 1023.,
+                ((packed >> 20) & 0x3ff) as f32 / 1023.,
+                (packed >> 30) as f32 / 3.,
+            ]
+        }
+        TextureFormat::R16Float => {
+            let value = f16(0);
+            [value, value, value, 1.]
+        }
+        TextureFormat::Rgba16Float => [f16(0), f16(2), f16(4), f16(6)],
+        TextureFormat::R32Float | TextureFormat::Depth32Float => {
+            let value = f32(0);
+            [value, value, value, 1.]
+        }
+        TextureFormat::Rgba32Float => [f32(0), f32(4), f32(8), f32(12)],
+        _ => return None,
+    };
+    Some(color)
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        31 if mantissa == 0. => f32::INFINITY,
+        31 => f32::NAN,
+        _ => (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+    };
+    if bits & 0x8000 == 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+pub(crate) fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figures::Size;
+    use wgpu::{TextureAspect, TextureFormat};
+
+    use super::{f16_to_f32, linear_to_srgb, srgb_to_linear, ImageAlpha};
+    use crate::readback::TextureData;
+
+    fn texel_image(format: TextureFormat, bytes: &[u8], alpha: ImageAlpha) -> [u8; 4] {
+        let data = TextureData {
+            format,
+            aspect: TextureAspect::All,
+            size: Size::new(1, 1),
+            bytes_per_row: bytes.len() as u32,
+            bytes: bytes.to_vec(),
+        };
+        let image = data.to_rgba_image(alpha).unwrap();
+        image.get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn f16_normal_values() {
+        assert_eq!(f16_to_f32(0x3c00), 1.);
+        assert_eq!(f16_to_f32(0xc000), -2.);
+        assert_eq!(f16_to_f32(0x3555), 0.333_251_95);
+        assert_eq!(f16_to_f32(0x7bff), 65504.);
+        assert_eq!(f16_to_f32(0x0400), 2f32.powi(-14));
+    }
+
+    #[test]
+    fn f16_zeros_and_subnormals() {
+        assert_eq!(f16_to_f32(0x0000).to_bits(), 0f32.to_bits());
+        assert_eq!(f16_to_f32(0x8000).to_bits(), (-0f32).to_bits());
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x8001), -(2f32.powi(-24)));
+        assert_eq!(f16_to_f32(0x03ff), 1023. * 2f32.powi(-24));
+    }
+
+    #[test]
+    fn f16_infinities_and_nan() {
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7e00).is_nan());
+        assert!(f16_to_f32(0xfc01).is_nan());
+    }
+
+    #[test]
+    fn srgb_curve_endpoints() {
+        assert_eq!(srgb_to_linear(0.), 0.);
+        assert_eq!(linear_to_srgb(0.), 0.);
+        assert!((srgb_to_linear(1.) - 1.).abs() < 1e-6);
+        assert!((linear_to_srgb(1.) - 1.).abs() < 1e-6);
+        // The linear segment and the curve meet at the thresholds.
+        assert!((srgb_to_linear(0.04045) - srgb_to_linear(0.040_450_1)).abs() < 1e-6);
+        assert!((linear_to_srgb(0.003_130_8) - linear_to_srgb(0.003_130_9)).abs() < 1e-5);
+        for value in [0.01, 0.2, 0.5, 0.73, 0.99] {
+            assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn premultiplied_texels_are_unpremultiplied() {
+        assert_eq!(
+            texel_image(
+                TextureFormat::Rgba8Unorm,
+                &[64, 32, 0, 128],
+                ImageAlpha::Premultiplied
+            ),
+            [128, 64, 0, 128]
+        );
+        assert_eq!(
+            texel_image(
+                TextureFormat::Rgba8Unorm,
+                &[64, 32, 0, 128],
+                ImageAlpha::Straight
+            ),
+            [64, 32, 0, 128]
+        );
+        // Fully transparent texels keep their channels instead of dividing by
+        // zero.
+        assert_eq!(
+            texel_image(
+                TextureFormat::Bgra8Unorm,
+                &[3, 2, 1, 0],
+                ImageAlpha::Premultiplied
+            ),
+            [1, 2, 3, 0]
+        );
+        assert_eq!(
+            texel_image(
+                TextureFormat::Rgba8Unorm,
+                &[10, 20, 30, 40],
+                ImageAlpha::Opaque
+            ),
+            [10, 20, 30, 255]
+        );
+    }
+
+    #[test]
+    fn premultiplied_srgb_texels_are_unpremultiplied_in_linear_space() {
+        // Linear 0.5 premultiplied by an alpha of 0.5 is linear 0.25.
+        let premultiplied = (linear_to_srgb(0.25) * 255.).round() as u8;
+        let straight = (linear_to_srgb(0.5) * 255.).round();
+        let [r, _, _, a] = texel_image(
+            TextureFormat::Rgba8UnormSrgb,
+            &[premultiplied, 0, 0, 128],
+            ImageAlpha::Premultiplied,
+        );
+        assert!((f32::from(r) - straight).abs() <= 1.);
+        assert_eq!(a, 128);
+    }
+
+    #[test]
+    fn float_texels_are_clamped_and_srgb_encoded() {
+        let texel = [0x0000u16, 0x3c00, 0x7c00, 0x3c00]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texel_image(TextureFormat::Rgba16Float, &texel, ImageAlpha::Straight),
+            [0, 255, 255, 255]
+        );
+
+        let texel = [0.214_041_14f32, -1., f32::NAN, 1.]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        let [r, g, b, a] = texel_image(TextureFormat::Rgba32Float, &texel, ImageAlpha::Straight);
+        assert_eq!(r, 128);
+        assert_eq!((g, b, a), (0, 0, 255));
+    }
+}
@@ -32,6 +32,17 @@ impl Frame {
         )
     }
 
+    /// Begins a render pass on `target`. If the target has a multisample
+    /// target, the pass renders into it and resolves to the target's color
+    /// component. Otherwise, the pass renders to the color component directly.
+    pub fn resolving_pass<'a>(
+        &'a mut self,
+        op: PassOp,
+        target: &'a impl RenderTarget,
+    ) -> wgpu::RenderPass<'a> {
+        self.pass(op, target, target.multisample_target())
+    }
+
     pub fn copy(&mut self, src: &UniformBuffer, dst: &UniformBuffer) {
         self.encoder.copy_buffer_to_buffer(
             &src.wgpu,
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use figures::Size;
+use image::imageops::FilterType;
+use image::{Rgba, Rgba32FImage, RgbaImage};
+use wgpu::TextureFormat;
+
+use crate::device::{label_or, Device};
+use crate::error::Error;
+use crate::export::{linear_to_srgb, srgb_to_linear};
+use crate::renderer::Renderer;
+use crate::texture::{Texture, TextureOptions};
+
+impl Device {
+    /// Decodes an encoded image, such as a PNG or JPEG, and uploads it to a
+    /// new texture. See [`TextureOptions`] for how the texture is created.
+    ///
+    /// The image is uploaded using the device's queue, and is available to
+    /// commands submitted afterwards. Mipmaps are generated on the CPU by
+    /// filtering linear colors premultiplied by alpha, so sRGB images don't
+    /// darken and transparent texels don't bleed into their neighbors.
+    pub fn create_texture_from_image(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        let mut image = image::load_from_memory(bytes)
+            .map_err(|err| Error::Image(Arc::new(err)))?
+            .into_rgba8();
+        let mut linear = if options.generate_mipmaps {
+            Some(premultiplied_linear(&image, options.srgb))
+        } else {
+            None
+        };
+        if options.premultiply_alpha {
+            premultiply(&mut image, options.srgb);
+        }
+
+        let size = Size::new(image.width(), image.height());
+        let format = if options.srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        };
+        let mip_level_count = if options.generate_mipmaps {
+            Texture::full_mip_level_count(size)
+        } else {
+            1
+        };
+        let label = label_or(options.label.as_deref(), || {
+            format!("easygpu image texture {}x{}", size.width, size.height)
+        });
+        let texture = self.create_texture_with_mip_levels(
+            size,
+            format,
+            options.usage | wgpu::TextureUsages::COPY_DST,
+            1,
+            mip_level_count,
+            Some(&label),
+        );
+
+        for mip_level in 0..mip_level_count {
+            if let Some(linear) = linear.as_mut().filter(|_| mip_level > 0) {
+                *linear = next_mip_level(linear);
+                image = texels_from_linear(linear, options.srgb, options.premultiply_alpha);
+            }
+            self.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture.wgpu,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * image.width()),
+                    rows_per_image: Some(image.height()),
+                },
+                wgpu::Extent3d {
+                    width: image.width(),
+                    height: image.height(),
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(texture)
+    }
+}
+
+impl Renderer {
+    /// Decodes an encoded image, such as a PNG or JPEG, and uploads it to a
+    /// new texture. See [`Device::create_texture_from_image`].
+    pub fn texture_from_image(
+        &self,
+        bytes: &[u8],
+        options: &TextureOptions,
+    ) -> Result<Texture, Error> {
+        self.device.create_texture_from_image(bytes, options)
+    }
+}
+
+/// Multiplies the color channels of `image` by its alpha channel. sRGB
+/// encoded colors are multiplied in linear space.
+fn premultiply(image: &mut RgbaImage, srgb: bool) {
+    for pixel in image.pixels_mut() {
+        let alpha = f32::from(pixel[3]) / 255.;
+        for channel in &mut pixel.0[..3] {
+            let value = f32::from(*channel) / 255.;
+            let value = if srgb {
+                linear_to_srgb(srgb_to_linear(value) * alpha)
+            } else {
+                value * alpha
+            };
+            *channel = (value * 255.).round() as u8;
+        }
+    }
+}
+
+/// Converts the colors of `image` to linear values multiplied by alpha, which
+/// can be filtered.
+fn premultiplied_linear(image: &RgbaImage, srgb: bool) -> Rgba32FImage {
+    Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let pixel = image.get_pixel(x, y);
+        let alpha = f32::from(pixel[3]) / 255.;
+        let mut linear = [0., 0., 0., alpha];
+        for (value, channel) in linear.iter_mut().zip(&pixel.0[..3]) {
+            let color = f32::from(*channel) / 255.;
+            *value = if srgb { srgb_to_linear(color) } else { color } * alpha;
+        }
+        Rgba(linear)
+    })
+}
+
+/// Returns the mip level after `linear`, which is half its size.
+fn next_mip_level(linear: &Rgba32FImage) -> Rgba32FImage {
+    image::imageops::resize(
+        linear,
+        (linear.width() / 2).max(1),
+        (linear.height() / 2).max(1),
+        FilterType::Triangle,
+    )
+}
+
+/// Converts premultiplied linear colors back to texels, sRGB encoded if
+/// `srgb`, and divided by alpha unless `premultiplied`.
+fn texels_from_linear(linear: &Rgba32FImage, srgb: bool, premultiplied: bool) -> RgbaImage {
+    RgbaImage::from_fn(linear.width(), linear.height(), |x, y| {
+        let pixel = linear.get_pixel(x, y);
+        let alpha = pixel[3].clamp(0., 1.);
+        let mut texel = [0, 0, 0, (alpha * 255.).round() as u8];
+        for (channel, value) in texel.iter_mut().zip(&pixel.0[..3]) {
+            let mut color = *value;
+            if !premultiplied {
+                color = if alpha > 0. { color / alpha } else { 0. };
+            }
+            let color = color.clamp(0., 1.);
+            let color = if srgb { linear_to_srgb(color) } else { color };
+            *channel = (color * 255.).round() as u8;
+        }
+        Rgba(texel)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::{next_mip_level, premultiplied_linear, texels_from_linear};
+
+    fn downsample(image: &RgbaImage, srgb: bool, premultiplied: bool) -> Rgba<u8> {
+        let linear = next_mip_level(&premultiplied_linear(image, srgb));
+        *texels_from_linear(&linear, srgb, premultiplied).get_pixel(0, 0)
+    }
+
+    #[test]
+    fn srgb_colors_are_averaged_in_linear_space() {
+        let image = RgbaImage::from_fn(2, 2, |x, _| {
+            let value = if x == 0 { 0 } else { 255 };
+            Rgba([value, value, value, 255])
+        });
+
+        assert_eq!(downsample(&image, true, false), Rgba([188, 188, 188, 255]));
+        assert_eq!(downsample(&image, false, false), Rgba([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn transparent_colors_dont_bleed() {
+        let image = RgbaImage::from_fn(2, 2, |x, y| {
+            if (x, y) == (0, 0) {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 255, 0, 0])
+            }
+        });
+
+        assert_eq!(downsample(&image, true, false), Rgba([255, 0, 0, 64]));
+        assert_eq!(downsample(&image, true, true), Rgba([137, 0, 0, 64]));
+    }
+}
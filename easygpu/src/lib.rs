@@ -1,17 +1,28 @@
 #![warn(clippy::all)]
 
+pub mod adapter;
 pub mod binding;
 pub mod buffers;
 pub mod canvas;
 pub mod color;
+#[cfg(any(feature = "ktx2", feature = "ddsfile"))]
+mod container;
 pub mod device;
 pub mod error;
+#[cfg(feature = "image")]
+pub mod export;
 pub mod frame;
+#[cfg(feature = "image")]
+mod import;
+mod mipmap;
 pub mod pipeline;
+pub mod readback;
 pub mod renderable;
 pub mod renderer;
+pub mod report;
 pub mod sampler;
 pub mod shader;
+pub mod surface;
 pub mod texture;
 pub mod transform;
 pub mod vertex;
@@ -19,18 +30,24 @@ pub mod vertex;
 pub use {figures, wgpu};
 
 pub mod prelude {
+    pub use super::adapter::*;
     pub use super::binding::*;
     pub use super::buffers::*;
     pub use super::canvas::*;
     pub use super::color::*;
     pub use super::device::*;
     pub use super::error::*;
+    #[cfg(feature = "image")]
+    pub use super::export::*;
     pub use super::frame::*;
     pub use super::pipeline::*;
+    pub use super::readback::*;
     pub use super::renderable::*;
     pub use super::renderer::*;
+    pub use super::report::*;
     pub use super::sampler::*;
     pub use super::shader::*;
+    pub use super::surface::*;
     pub use super::texture::*;
     pub use super::transform::*;
     pub use super::vertex::*;
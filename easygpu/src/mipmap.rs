@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use wgpu::TextureFormat;
+
+use crate::device::Device;
+
+/// The pipeline used to render each mip level of textures of a single format
+/// from the previous mip level.
+#[derive(Debug)]
+pub(crate) struct MipmapPipeline {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+impl MipmapPipeline {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_from_wgsl(
+            include_str!("shaders/downsample.wgsl"),
+            Some("easygpu mipmap shader"),
+        );
+        let layout = device
+            .wgpu
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("easygpu mipmap layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let pipeline_layout = device
+            .wgpu
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("easygpu mipmap pipeline layout"),
+                bind_group_layouts: &[&layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = device
+            .wgpu
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(&format!("easygpu mipmap pipeline {:?}", format)),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader.wgpu,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader.wgpu,
+                    entry_point: "fs_main",
+                    targets: &[Some(format.into())],
+                }),
+                multiview: None,
+            });
+        let sampler = device.wgpu.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("easygpu mipmap sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            layout,
+            sampler,
+        }
+    }
+}
+
+/// The views and bind groups used to render each mip level of a texture.
+#[derive(Debug)]
+pub(crate) struct MipmapChain {
+    pipeline: Arc<MipmapPipeline>,
+    /// The view of each mip level after the first, rendered to.
+    targets: Vec<wgpu::TextureView>,
+    /// The bind group sampling the mip level before each target.
+    sources: Vec<wgpu::BindGroup>,
+}
+
+impl MipmapChain {
+    /// Returns the chain used to generate the mip levels of `texture`, or
+    /// `None` if it can't be both sampled and rendered to.
+    pub fn new(
+        device: &Device,
+        texture: &wgpu::Texture,
+        mip_level_count: u32,
+        label: &str,
+    ) -> Option<Self> {
+        let format = texture.format();
+        let features = device.adapter.get_texture_format_features(format);
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+        if format.has_depth_aspect()
+            || !texture.usage().contains(usage)
+            || !features
+                .allowed_usages
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            || !features
+                .flags
+                .contains(wgpu::TextureFormatFeatureFlags::FILTERABLE)
+        {
+            return None;
+        }
+        let pipeline = device.mipmap_pipeline(format);
+
+        let views = (0..mip_level_count)
+            .map(|mip_level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some(&format!("{} mip {}", label, mip_level)),
+                    base_mip_level: mip_level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+        let sources = views[..views.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(mip_level, view)| {
+                device.wgpu.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&format!("{} mip {} source", label, mip_level)),
+                    layout: &pipeline.layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&pipeline.sampler),
+                        },
+                    ],
+                })
+            })
+            .collect();
+        let targets = views.into_iter().skip(1).collect();
+
+        Some(Self {
+            pipeline,
+            targets,
+            sources,
+        })
+    }
+
+    /// Records the render passes rendering each mip level from the previous
+    /// one.
+    pub fn encode(&self, encoder: &mut wgpu::CommandEncoder) {
+        for (target, source) in self.targets.iter().zip(&self.sources) {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("easygpu mipmap pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline.pipeline);
+            pass.set_bind_group(0, source, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
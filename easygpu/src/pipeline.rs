@@ -116,6 +116,9 @@ pub trait AbstractPipeline<'a>: Deref<Target = PipelineCore> {
 
 #[derive(Debug)]
 pub struct PipelineDescription<'a> {
+    /// The debug label of the pipeline. Its layouts and shaders are labeled
+    /// after it.
+    pub label: Option<&'a str>,
     pub vertex_layout: &'a [VertexFormat],
     pub pipeline_layout: &'a [Set<'a>],
     pub vertex_shader: &'static [u8],
@@ -0,0 +1,494 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use figures::{Point, Size, SizedRect};
+use wgpu::{TextureAspect, TextureFormat};
+
+use crate::error::Error;
+use crate::texture::{copy_aspect, TexelLayout};
+use crate::transform::ScreenSpace;
+
+/// Texel data read back from a [`Texture`](crate::texture::Texture).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureData {
+    pub format: TextureFormat,
+    /// The aspect of the texture that was read, such as
+    /// [`TextureAspect::DepthOnly`] for depth textures.
+    pub aspect: TextureAspect,
+    /// The size of the region that was read, in texels.
+    pub size: Size<u32, ScreenSpace>,
+    /// The number of bytes in each row of `bytes`. Rows are tightly packed.
+    pub bytes_per_row: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl TextureData {
+    /// Returns the texels reinterpreted as `T`, such as
+    /// [`Bgra8`](crate::color::Bgra8) for a `Bgra8Unorm` texture or `f32` for
+    /// a `Depth32Float` texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of `T` doesn't evenly divide the data.
+    pub fn texels<T: bytemuck::Pod>(&self) -> Vec<T> {
+        collect_pod(&self.bytes)
+    }
+
+    /// Returns the bytes of the row of blocks at index `row`. For
+    /// uncompressed formats, this is the row of texels at `row`.
+    pub fn row(&self, row: u32) -> &[u8] {
+        let start = (row * self.bytes_per_row) as usize;
+        &self.bytes[start..start + self.bytes_per_row as usize]
+    }
+}
+
+/// Copies `bytes` into a `Vec<T>`, which unlike casting doesn't require
+/// `bytes` to be aligned for `T`.
+///
+/// # Panics
+///
+/// Panics if the size of `T` doesn't evenly divide `bytes`.
+pub(crate) fn collect_pod<T: bytemuck::Pod>(bytes: &[u8]) -> Vec<T> {
+    let size = std::mem::size_of::<T>();
+    assert!(
+        bytes.len() % size == 0,
+        "element size does not evenly divide the data"
+    );
+    let mut values = vec![T::zeroed(); bytes.len() / size];
+    bytemuck::cast_slice_mut(&mut values).copy_from_slice(bytes);
+    values
+}
+
+/// A pool of staging buffers used to read buffers back from the GPU, so that
+/// repeated reads don't allocate a new buffer each time.
+#[derive(Debug, Default)]
+pub(crate) struct StagingPool {
+    buffers: Vec<wgpu::Buffer>,
+}
+
+impl StagingPool {
+    /// The maximum number of buffers kept for reuse.
+    const CAPACITY: usize = 4;
+
+    /// Returns a mappable buffer of at least `size` bytes.
+    pub fn take(&mut self, device: &wgpu::Device, size: wgpu::BufferAddress) -> wgpu::Buffer {
+        if let Some(index) = self.buffers.iter().position(|buffer| buffer.size() >= size) {
+            return self.buffers.swap_remove(index);
+        }
+
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("easygpu staging buffer"),
+            size: size.next_power_of_two().max(256),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Returns `buffer`, which must be unmapped, to the pool. When the pool is
+    /// full, its smallest buffer is dropped.
+    pub fn recycle(&mut self, buffer: wgpu::Buffer) {
+        self.buffers.push(buffer);
+        if self.buffers.len() > Self::CAPACITY {
+            let smallest = self
+                .buffers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, buffer)| buffer.size())
+                .map(|(index, _)| index)
+                .unwrap();
+            self.buffers.swap_remove(smallest);
+        }
+    }
+}
+
+/// A pending read of a texture's data, created by
+/// [`Renderer::read_async`](crate::renderer::Renderer::read_async).
+///
+/// The request completes once the GPU has copied the data and the device has
+/// been polled, either by awaiting it or by checking
+/// [`ReadbackRequest::try_take`] each frame. On native platforms, wgpu only
+/// completes the request when the device is polled, such as by
+/// [`Device::poll`](crate::device::Device::poll) or by submitting more work.
+#[derive(Debug)]
+pub struct ReadbackRequest {
+    /// The buffer being mapped, which is `None` once the request completed.
+    buffer: Option<wgpu::Buffer>,
+    layout: ReadbackLayout,
+    state: Arc<Mutex<MapState>>,
+}
+
+#[derive(Debug, Default)]
+struct MapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+impl ReadbackRequest {
+    /// Starts mapping `buffer`, which the region described by `layout` must
+    /// have been copied into.
+    pub(crate) fn new(buffer: wgpu::Buffer, layout: ReadbackLayout) -> Self {
+        let state = Arc::new(Mutex::new(MapState::default()));
+        let callback_state = state.clone();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let mut state = callback_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+
+        Self {
+            buffer: Some(buffer),
+            layout,
+            state,
+        }
+    }
+
+    /// Returns true if the data is ready to be taken.
+    pub fn is_ready(&self) -> bool {
+        self.state.lock().unwrap().result.is_some()
+    }
+
+    /// Returns the data if the read has completed, without blocking. Returns
+    /// `None` if the read is still pending or the data has already been taken.
+    pub fn try_take(&mut self) -> Option<Result<TextureData, Error>> {
+        let result = self.state.lock().unwrap().result.take()?;
+        Some(self.finish(result))
+    }
+
+    fn finish(&mut self, result: Result<(), wgpu::BufferAsyncError>) -> Result<TextureData, Error> {
+        let buffer = self
+            .buffer
+            .take()
+            .expect("ReadbackRequest polled after completion");
+        result?;
+        let data = self.layout.unpad(&buffer.slice(..).get_mapped_range());
+        buffer.unmap();
+        Ok(data)
+    }
+}
+
+impl Future for ReadbackRequest {
+    type Output = Result<TextureData, Error>;
+
+    /// # Panics
+    ///
+    /// Panics if polled after returning [`Poll::Ready`], or after the data was
+    /// taken using [`ReadbackRequest::try_take`].
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        assert!(
+            self.buffer.is_some(),
+            "ReadbackRequest polled after completion"
+        );
+        let result = {
+            let mut state = self.state.lock().unwrap();
+            match state.result.take() {
+                Some(result) => result,
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+        Poll::Ready(self.finish(result))
+    }
+}
+
+/// The layout of a texture region when copied into a buffer, with each row
+/// padded to [`wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReadbackLayout {
+    pub format: TextureFormat,
+    pub aspect: TextureAspect,
+    pub mip_level: u32,
+    pub origin: wgpu::Origin3d,
+    pub extent: wgpu::Extent3d,
+    /// The number of rows of blocks in the region.
+    pub rows: u32,
+    pub bytes_per_row: u32,
+    pub padded_bytes_per_row: u32,
+}
+
+impl ReadbackLayout {
+    /// Returns the layout used to read `rect` of `texture`'s mip level
+    /// `mip_level`. When `rect` is `None`, the entire mip level is read.
+    ///
+    /// Regions of compressed textures must be aligned to the format's blocks,
+    /// except where they end at the edge of the mip level.
+    pub fn new(
+        texture: &wgpu::Texture,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<Self, Error> {
+        check_readable(texture.usage(), texture.sample_count())?;
+        if mip_level >= texture.mip_level_count() {
+            return Err(Error::InvalidReadbackRegion);
+        }
+
+        Self::for_region(
+            texture.format(),
+            texture
+                .size()
+                .mip_level_size(mip_level, texture.dimension()),
+            rect,
+            mip_level,
+        )
+    }
+
+    /// Returns the layout used to read `rect` of a mip level of `format`
+    /// whose size is `mip_size`.
+    fn for_region(
+        format: TextureFormat,
+        mip_size: wgpu::Extent3d,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<Self, Error> {
+        let aspect = copy_aspect(format);
+        let block_size = format
+            .block_size(Some(aspect))
+            .ok_or(Error::UnsupportedReadbackFormat(format))?;
+        let (block_width, block_height) = format.block_dimensions();
+
+        let rect = rect.unwrap_or_else(|| {
+            SizedRect::new(Point::default(), Size::new(mip_size.width, mip_size.height))
+        });
+        if rect.size.width == 0 || rect.size.height == 0 {
+            return Err(Error::InvalidReadbackRegion);
+        }
+        let copy_size =
+            TexelLayout::copy_size(format, mip_size, rect).ok_or(Error::InvalidReadbackRegion)?;
+        let (width, height) = (copy_size.width, copy_size.height);
+        let (x, y) = (rect.origin.x, rect.origin.y);
+
+        let bytes_per_row = width / block_width * block_size;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = bytes_per_row.div_ceil(alignment) * alignment;
+
+        Ok(Self {
+            format,
+            aspect,
+            mip_level,
+            origin: wgpu::Origin3d { x, y, z: 0 },
+            extent: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            rows: height / block_height,
+            bytes_per_row,
+            padded_bytes_per_row,
+        })
+    }
+
+    /// The size of the buffer the region is copied into.
+    pub fn buffer_size(&self) -> u64 {
+        u64::from(self.padded_bytes_per_row) * u64::from(self.rows)
+    }
+
+    /// Records a copy of the region of `texture` into `buffer`.
+    pub fn copy(
+        &self,
+        texture: &wgpu::Texture,
+        buffer: &wgpu::Buffer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: self.mip_level,
+                origin: self.origin,
+                aspect: self.aspect,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(self.rows),
+                },
+            },
+            self.extent,
+        );
+    }
+
+    /// Strips the row padding from the mapped contents of the buffer.
+    pub fn unpad(&self, padded: &[u8]) -> TextureData {
+        let mut bytes = Vec::with_capacity((self.bytes_per_row * self.rows) as usize);
+        for row in padded
+            .chunks(self.padded_bytes_per_row as usize)
+            .take(self.rows as usize)
+        {
+            bytes.extend_from_slice(&row[..self.bytes_per_row as usize]);
+        }
+
+        TextureData {
+            format: self.format,
+            aspect: self.aspect,
+            size: Size::new(self.extent.width, self.extent.height),
+            bytes_per_row: self.bytes_per_row,
+            bytes,
+        }
+    }
+}
+
+/// Returns [`Error::TextureNotReadable`] unless a texture with `usage` and
+/// `sample_count` can be copied to a buffer.
+fn check_readable(usage: wgpu::TextureUsages, sample_count: u32) -> Result<(), Error> {
+    if usage.contains(wgpu::TextureUsages::COPY_SRC) && sample_count == 1 {
+        Ok(())
+    } else {
+        Err(Error::TextureNotReadable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figures::{Point, Size, SizedRect};
+    use wgpu::{Extent3d, TextureAspect, TextureDimension, TextureFormat};
+
+    use super::{check_readable, ReadbackLayout};
+    use crate::buffers::DepthBuffer;
+    use crate::error::Error;
+
+    fn extent(width: u32, height: u32) -> Extent3d {
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        }
+    }
+
+    /// Returns a buffer of `rows` padded rows, where each byte of a row's data
+    /// is its row index and each padding byte is 0xff.
+    fn padded_rows(layout: &ReadbackLayout) -> Vec<u8> {
+        let mut padded = Vec::new();
+        for row in 0..layout.rows {
+            let start = padded.len();
+            padded.resize(start + layout.bytes_per_row as usize, row as u8);
+            padded.resize(start + layout.padded_bytes_per_row as usize, 0xff);
+        }
+        padded
+    }
+
+    #[test]
+    fn rows_are_padded_to_the_copy_alignment() {
+        let layout =
+            ReadbackLayout::for_region(TextureFormat::Rgba8Unorm, extent(10, 3), None, 0).unwrap();
+        assert_eq!(layout.bytes_per_row, 40);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.rows, 3);
+        assert_eq!(layout.buffer_size(), 768);
+
+        let data = layout.unpad(&padded_rows(&layout));
+        assert_eq!(data.size, Size::new(10, 3));
+        assert_eq!(data.bytes_per_row, 40);
+        assert_eq!(data.bytes.len(), 120);
+        for row in 0..3 {
+            assert!(data.row(row).iter().all(|&byte| byte == row as u8));
+        }
+    }
+
+    #[test]
+    fn aligned_rows_are_not_padded() {
+        let layout =
+            ReadbackLayout::for_region(TextureFormat::Rgba8Unorm, extent(64, 2), None, 0).unwrap();
+        assert_eq!(layout.bytes_per_row, 256);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+    }
+
+    #[test]
+    fn sub_rectangles_are_offset_and_bounds_checked() {
+        let rect = SizedRect::new(Point::new(5, 7), Size::new(3, 2));
+        let layout =
+            ReadbackLayout::for_region(TextureFormat::R32Float, extent(256, 256), Some(rect), 0)
+                .unwrap();
+        assert_eq!((layout.origin.x, layout.origin.y), (5, 7));
+        assert_eq!((layout.extent.width, layout.extent.height), (3, 2));
+        assert_eq!(layout.bytes_per_row, 12);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.unpad(&padded_rows(&layout)).bytes.len(), 24);
+
+        let outside = SizedRect::new(Point::new(250, 0), Size::new(7, 1));
+        assert!(matches!(
+            ReadbackLayout::for_region(TextureFormat::R32Float, extent(256, 256), Some(outside), 0),
+            Err(Error::InvalidReadbackRegion)
+        ));
+        let empty = SizedRect::new(Point::new(0, 0), Size::new(0, 1));
+        assert!(matches!(
+            ReadbackLayout::for_region(TextureFormat::R32Float, extent(256, 256), Some(empty), 0),
+            Err(Error::InvalidReadbackRegion)
+        ));
+    }
+
+    #[test]
+    fn mip_levels_use_their_own_size() {
+        let mip_size = extent(100, 60).mip_level_size(2, TextureDimension::D2);
+        let layout = ReadbackLayout::for_region(TextureFormat::R8Unorm, mip_size, None, 2).unwrap();
+        assert_eq!(layout.mip_level, 2);
+        assert_eq!((layout.extent.width, layout.extent.height), (25, 15));
+        assert_eq!(layout.bytes_per_row, 25);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.rows, 15);
+    }
+
+    #[test]
+    fn compressed_regions_are_copied_in_whole_blocks() {
+        // A 30x30 texture is stored as 8x8 blocks of 4x4 texels, 8 bytes each.
+        let layout =
+            ReadbackLayout::for_region(TextureFormat::Bc1RgbaUnorm, extent(30, 30), None, 0)
+                .unwrap();
+        assert_eq!((layout.extent.width, layout.extent.height), (32, 32));
+        assert_eq!(layout.rows, 8);
+        assert_eq!(layout.bytes_per_row, 64);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.unpad(&padded_rows(&layout)).bytes.len(), 512);
+
+        let mip_size = extent(30, 30).mip_level_size(1, TextureDimension::D2);
+        let layout =
+            ReadbackLayout::for_region(TextureFormat::Bc1RgbaUnorm, mip_size, None, 1).unwrap();
+        assert_eq!((layout.extent.width, layout.extent.height), (16, 16));
+        assert_eq!(layout.rows, 4);
+
+        let blocks = SizedRect::new(Point::new(4, 8), Size::new(8, 4));
+        let layout = ReadbackLayout::for_region(
+            TextureFormat::Bc1RgbaUnorm,
+            extent(30, 30),
+            Some(blocks),
+            0,
+        )
+        .unwrap();
+        assert_eq!(layout.rows, 1);
+        assert_eq!(layout.bytes_per_row, 16);
+
+        let unaligned = SizedRect::new(Point::new(2, 0), Size::new(4, 4));
+        assert!(matches!(
+            ReadbackLayout::for_region(
+                TextureFormat::Bc1RgbaUnorm,
+                extent(30, 30),
+                Some(unaligned),
+                0
+            ),
+            Err(Error::InvalidReadbackRegion)
+        ));
+    }
+
+    #[test]
+    fn depth_buffers_are_read_by_their_depth_aspect() {
+        assert!(check_readable(DepthBuffer::USAGE, 1).is_ok());
+        assert!(matches!(
+            check_readable(DepthBuffer::USAGE, 4),
+            Err(Error::TextureNotReadable)
+        ));
+
+        let layout =
+            ReadbackLayout::for_region(DepthBuffer::FORMAT, extent(30, 20), None, 0).unwrap();
+        assert_eq!(layout.aspect, TextureAspect::DepthOnly);
+        assert_eq!(layout.bytes_per_row, 120);
+        assert_eq!(layout.padded_bytes_per_row, 256);
+        assert_eq!(layout.rows, 20);
+    }
+}
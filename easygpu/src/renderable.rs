@@ -2,9 +2,12 @@ use crate::buffers::VertexBuffer;
 use crate::renderer::Renderer;
 
 pub trait Renderable {
-    fn buffer(&self, r: &Renderer) -> VertexBuffer;
+    /// The type of vertices in the buffer.
+    type Vertex: bytemuck::Pod;
 
-    fn finish(self, r: &Renderer) -> VertexBuffer
+    fn buffer(&self, r: &Renderer) -> VertexBuffer<Self::Vertex>;
+
+    fn finish(self, r: &Renderer) -> VertexBuffer<Self::Vertex>
     where
         Self: std::marker::Sized,
     {
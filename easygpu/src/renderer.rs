@@ -1,18 +1,27 @@
+use std::mem;
 use std::ops::Range;
 use std::sync::{Arc, Mutex};
 
 use figures::{Pixels, Size, SizedRect};
-use wgpu::{FilterMode, MultisampleState, TextureAspect, TextureFormat, TextureViewDescriptor};
+use wgpu::{FilterMode, MultisampleState, TextureFormat};
 
+use crate::adapter::AdapterPolicy;
 use crate::binding::{Bind, BindingGroup, BindingGroupLayout};
-use crate::buffers::{DepthBuffer, Framebuffer, IndexBuffer, UniformBuffer, VertexBuffer};
+use crate::buffers::{
+    DepthBuffer, DynamicUniformBuffer, Framebuffer, IndexBuffer, IndexType, StorageBuffer,
+    UniformBuffer, VertexBuffer,
+};
 use crate::canvas::Canvas;
-use crate::color::{Bgra8, Rgba};
-use crate::device::Device;
+use crate::color::{Bgra8, Rgba, Rgba8};
+use crate::device::{Device, DeviceOptions};
 use crate::error::Error;
 use crate::frame::Frame;
-use crate::pipeline::{AbstractPipeline, Blending};
-use crate::sampler::Sampler;
+use crate::pipeline::{AbstractPipeline, Blending, PipelineDescription};
+use crate::readback::{collect_pod, ReadbackLayout, ReadbackRequest, StagingPool, TextureData};
+use crate::report::AdapterReport;
+use crate::sampler::{Sampler, SamplerDescriptor};
+use crate::shader::Shader;
+use crate::surface::{SurfaceOptions, SurfaceTarget};
 use crate::texture::Texture;
 use crate::transform::ScreenSpace;
 use crate::vertex::VertexLayout;
@@ -26,63 +35,154 @@ pub struct Renderer {
     pub device: Device,
     /// Enables MSAA for values > 1.
     pub(crate) sample_count: u32,
+    staging: StagingPool,
 }
 
 impl Renderer {
     pub async fn for_surface(
         surface: wgpu::Surface,
         instance: &wgpu::Instance,
+        policy: &AdapterPolicy,
         sample_count: u32,
+        options: &DeviceOptions,
     ) -> Result<Self, Error> {
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .ok_or(Error::NoAdaptersFound)?;
+        let adapter = policy.select(instance, Some(&surface))?;
 
         Ok(Self {
-            device: Device::for_surface(surface, &adapter).await?,
+            device: Device::for_surface(surface, adapter, options).await?,
             sample_count,
+            staging: StagingPool::default(),
         })
     }
 
-    pub async fn offscreen(adapter: &wgpu::Adapter, sample_count: u32) -> Result<Self, Error> {
+    pub async fn offscreen(
+        adapter: wgpu::Adapter,
+        sample_count: u32,
+        options: &DeviceOptions,
+    ) -> Result<Self, Error> {
         Ok(Self {
-            device: Device::offscreen(adapter).await?,
+            device: Device::offscreen(adapter, options).await?,
             sample_count,
+            staging: StagingPool::default(),
         })
     }
 
+    /// Creates an offscreen renderer, creating its own [`wgpu::Instance`] and
+    /// selecting an adapter using `policy`.
+    pub async fn headless(
+        policy: &AdapterPolicy,
+        sample_count: u32,
+        options: &DeviceOptions,
+    ) -> Result<Self, Error> {
+        let instance = policy.instance();
+        let adapter = policy.select(&instance, None)?;
+
+        Self::offscreen(adapter, sample_count, options).await
+    }
+
+    /// Returns a report of the adapter and device capabilities.
+    pub fn report(&self) -> AdapterReport {
+        self.device.report()
+    }
+
     pub const fn sample_count(&self) -> u32 {
         self.sample_count
     }
 
+    /// Changes the sample count used for MSAA. Pipelines and multisampled
+    /// textures created with the previous sample count must be recreated, and
+    /// surfaces other than the device's primary surface must be configured
+    /// again using [`Renderer::configure_surface`].
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        self.sample_count = sample_count;
+        self.update_surface_buffers();
+    }
+
     pub fn configure<PresentMode: Into<wgpu::PresentMode>>(
         &mut self,
         size: Size<u32, ScreenSpace>,
         mode: PresentMode,
         format: TextureFormat,
+    ) -> Result<(), Error> {
+        self.device.configure(size, mode, format)?;
+        self.update_surface_buffers();
+        Ok(())
+    }
+
+    pub fn configure_with_options(
+        &mut self,
+        size: Size<u32, ScreenSpace>,
+        options: &SurfaceOptions,
+    ) -> Result<(), Error> {
+        self.device.configure_with_options(size, options)?;
+        self.update_surface_buffers();
+        Ok(())
+    }
+
+    /// Attaches `surface` to this renderer's device as its primary surface,
+    /// returning the surface it replaces. See [`Device::set_surface`] for
+    /// details.
+    pub fn set_surface(&mut self, surface: wgpu::Surface) -> Result<Option<SurfaceTarget>, Error> {
+        self.device.set_surface(surface)
+    }
+
+    /// Returns the device's primary surface, or [`Error::NoSurface`] if it has
+    /// none.
+    pub fn surface(&self) -> Result<&SurfaceTarget, Error> {
+        self.device.surface.as_ref().ok_or(Error::NoSurface)
+    }
+
+    /// Creates a [`SurfaceTarget`] for an additional surface, such as another
+    /// window, sharing this renderer's device. The target must be configured
+    /// using [`Renderer::configure_surface`] before it can be rendered to.
+    pub fn add_surface(&self, surface: wgpu::Surface) -> Result<SurfaceTarget, Error> {
+        self.device.create_surface_target(surface)
+    }
+
+    /// Configures `target`, recreating its depth buffer and multisampled color
+    /// target if needed.
+    pub fn configure_surface(
+        &self,
+        target: &mut SurfaceTarget,
+        size: Size<u32, ScreenSpace>,
+        options: &SurfaceOptions,
     ) {
-        self.device.configure(size, mode, format)
-    }
-
-    pub fn current_frame(&self) -> Result<RenderFrame, wgpu::SurfaceError> {
-        let surface = self.device.surface.as_ref().unwrap();
-        let surface_texture = surface.get_current_texture()?;
-        let view = surface_texture
-            .texture
-            .create_view(&TextureViewDescriptor::default());
-        Ok(RenderFrame {
-            wgpu: Some(surface_texture),
-            view,
-            depth: self
-                .device
-                .create_zbuffer(self.device.size(), self.sample_count),
-            size: self.device.size(),
-        })
+        target.configure(&self.device.wgpu, size, options);
+        target.update_buffers(&self.device, self.sample_count);
+    }
+
+    /// Recreates the buffers sized to the primary surface if needed.
+    fn update_surface_buffers(&mut self) {
+        if let Some(mut surface) = self.device.surface.take() {
+            surface.update_buffers(&self.device, self.sample_count);
+            self.device.surface = Some(surface);
+        }
+    }
+
+    /// Returns the [`SurfaceOptions`] using the first of `formats` and
+    /// `present_modes` that the surface supports. See
+    /// [`SurfaceOptions::negotiate`] for details.
+    pub fn negotiate_surface_options(
+        &self,
+        formats: &[TextureFormat],
+        present_modes: &[wgpu::PresentMode],
+    ) -> Option<SurfaceOptions> {
+        self.device
+            .negotiate_surface_options(formats, present_modes)
+    }
+
+    /// Returns the next frame of `target`, such as the one returned by
+    /// [`Renderer::surface`].
+    ///
+    /// If the surface is lost or outdated, it is reconfigured with its last
+    /// configuration and the frame is requested again. Returns `Ok(None)` if
+    /// the frame should be skipped, which happens when acquiring the frame
+    /// timed out or the surface has a size of zero.
+    ///
+    /// Returns [`Error::SurfaceNotConfigured`] if the surface hasn't been
+    /// configured.
+    pub fn current_frame(&self, target: &SurfaceTarget) -> Result<Option<RenderFrame>, Error> {
+        target.current_frame(&self.device.wgpu)
     }
 
     pub fn texture(
@@ -91,45 +191,81 @@ impl Renderer {
         format: wgpu::TextureFormat,
         usage: wgpu::TextureUsages,
         multisampled: bool,
+        label: Option<&str>,
     ) -> Texture {
         let sample_count = if multisampled { self.sample_count } else { 1 };
         self.device
-            .create_texture(size, format, usage, sample_count)
+            .create_texture(size, format, usage, sample_count, label)
     }
 
     pub fn framebuffer(
         &self,
         size: Size<u32, ScreenSpace>,
         format: wgpu::TextureFormat,
+        label: Option<&str>,
     ) -> Framebuffer {
         self.device
-            .create_framebuffer(size, format, self.sample_count)
+            .create_framebuffer(size, format, self.sample_count, label)
     }
 
-    pub fn zbuffer(&self, size: Size<u32, ScreenSpace>) -> DepthBuffer {
-        self.device.create_zbuffer(size, self.sample_count)
+    pub fn zbuffer(&self, size: Size<u32, ScreenSpace>, label: Option<&str>) -> DepthBuffer {
+        self.device.create_zbuffer(size, self.sample_count, label)
     }
 
-    pub fn vertex_buffer<T: bytemuck::Pod>(&self, verts: &[T]) -> VertexBuffer
+    pub fn vertex_buffer<T>(&self, verts: &[T], label: Option<&str>) -> VertexBuffer<T>
     where
-        T: 'static + Copy,
+        T: bytemuck::Pod + 'static + Copy,
     {
-        self.device.create_buffer(verts)
+        self.device.create_buffer(verts, label)
     }
 
-    pub fn uniform_buffer<T>(&self, buf: &[T]) -> UniformBuffer
+    pub fn uniform_buffer<T>(&self, buf: &[T], label: Option<&str>) -> UniformBuffer
     where
         T: bytemuck::Pod + 'static + Copy,
     {
-        self.device.create_uniform_buffer(buf)
+        self.device.create_uniform_buffer(buf, label)
     }
 
-    pub fn binding_group(&self, layout: &BindingGroupLayout, binds: &[&dyn Bind]) -> BindingGroup {
-        self.device.create_binding_group(layout, binds)
+    pub fn dynamic_uniform_buffer<T>(
+        &self,
+        buf: &[T],
+        label: Option<&str>,
+    ) -> DynamicUniformBuffer<T>
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        self.device.create_dynamic_uniform_buffer(buf, label)
+    }
+
+    pub fn storage_buffer<T>(&self, buf: &[T], label: Option<&str>) -> StorageBuffer
+    where
+        T: bytemuck::Pod + 'static + Copy,
+    {
+        self.device.create_storage_buffer(buf, label)
     }
 
-    pub fn sampler(&self, min_filter: FilterMode, mag_filter: FilterMode) -> Sampler {
-        self.device.create_sampler(min_filter, mag_filter)
+    pub fn binding_group(
+        &self,
+        layout: &BindingGroupLayout,
+        binds: &[&dyn Bind],
+        label: Option<&str>,
+    ) -> BindingGroup {
+        self.device.create_binding_group(layout, binds, label)
+    }
+
+    pub fn sampler(
+        &self,
+        min_filter: FilterMode,
+        mag_filter: FilterMode,
+        label: Option<&str>,
+    ) -> Sampler {
+        self.device.create_sampler(min_filter, mag_filter, label)
+    }
+
+    /// Returns a sampler described by `descriptor`, shared with other samplers
+    /// created from an equal descriptor.
+    pub fn sampler_with_descriptor(&self, descriptor: &SamplerDescriptor) -> Sampler {
+        self.device.create_sampler_with_descriptor(descriptor)
     }
 
     pub fn pipeline<T>(&self, blending: Blending, format: TextureFormat) -> T
@@ -137,105 +273,264 @@ impl Renderer {
         T: AbstractPipeline<'static>,
     {
         let desc = T::description();
-        let pip_layout = self.device.create_pipeline_layout(desc.pipeline_layout);
+        let (vs_label, fs_label) = shader_labels(&desc);
+        let vs = self
+            .device
+            .create_shader(desc.vertex_shader, Some(&vs_label));
+        let fs = self
+            .device
+            .create_shader(desc.fragment_shader, Some(&fs_label));
+
+        self.setup_pipeline(&desc, &vs, &fs, blending, format)
+    }
+
+    /// Creates a pipeline, returning an error instead of panicking if its
+    /// shaders fail to compile or wgpu reports a validation error.
+    pub async fn try_pipeline<T>(
+        &self,
+        blending: Blending,
+        format: TextureFormat,
+    ) -> Result<T, Error>
+    where
+        T: AbstractPipeline<'static>,
+    {
+        let desc = T::description();
+        let (vs_label, fs_label) = shader_labels(&desc);
+        let vs = self
+            .device
+            .try_create_shader(desc.vertex_shader, Some(&vs_label))
+            .await?;
+        let fs = self
+            .device
+            .try_create_shader(desc.fragment_shader, Some(&fs_label))
+            .await?;
+
+        self.device
+            .capture_errors(|_| self.setup_pipeline(&desc, &vs, &fs, blending, format))
+            .await
+    }
+
+    fn setup_pipeline<T>(
+        &self,
+        desc: &PipelineDescription<'static>,
+        vs: &Shader,
+        fs: &Shader,
+        blending: Blending,
+        format: TextureFormat,
+    ) -> T
+    where
+        T: AbstractPipeline<'static>,
+    {
+        let pip_layout = self
+            .device
+            .create_pipeline_layout(desc.pipeline_layout, desc.label);
         let vertex_layout = VertexLayout::from(desc.vertex_layout);
-        let vs = self.device.create_shader(desc.vertex_shader);
-        let fs = self.device.create_shader(desc.fragment_shader);
 
         T::setup(
             self.device.create_pipeline(
                 pip_layout,
                 vertex_layout,
                 blending,
-                &vs,
-                &fs,
+                vs,
+                fs,
                 format,
                 MultisampleState {
                     count: self.sample_count,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
+                desc.label,
             ),
             &self.device,
         )
     }
 
-    pub fn read<F>(&mut self, fb: &Framebuffer, f: F) -> Result<(), wgpu::BufferAsyncError>
+    /// Sets the callback invoked when the device is lost.
+    pub fn on_device_lost<F>(&self, callback: F)
+    where
+        F: FnMut(Error) + Send + 'static,
+    {
+        self.device.on_device_lost(callback);
+    }
+
+    /// Reads the framebuffer's texels as [`Bgra8`] and passes them to `f`,
+    /// swizzling `Rgba8` texels. This blocks until the GPU has finished
+    /// rendering to the framebuffer.
+    ///
+    /// Framebuffers of formats other than `Bgra8Unorm`, `Rgba8Unorm` and their
+    /// sRGB variants return [`Error::UnsupportedReadbackFormat`]. Use
+    /// [`Renderer::read_texture`] to read them.
+    pub fn read<F>(&mut self, fb: &Framebuffer, f: F) -> Result<(), Error>
     where
         F: 'static + FnOnce(&[Bgra8]),
     {
-        let mut encoder = self.device.create_command_encoder();
+        let format = fb.texture.format;
+        let texels = match format {
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                self.read_texture(&fb.texture, None, 0)?.texels::<Bgra8>()
+            }
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => self
+                .read_texture(&fb.texture, None, 0)?
+                .texels::<Rgba8>()
+                .into_iter()
+                .map(Bgra8::from)
+                .collect(),
+            other => return Err(Error::UnsupportedReadbackFormat(other)),
+        };
+        f(&texels);
+
+        Ok(())
+    }
+
+    /// Reads `rect` of `texture`'s mip level `mip_level`, or the entire mip
+    /// level if `rect` is `None`. This blocks until the GPU has finished
+    /// writing to the texture.
+    ///
+    /// The texture must have been created with
+    /// [`wgpu::TextureUsages::COPY_SRC`]. Depth and stencil textures are read
+    /// using their depth or stencil aspect.
+    pub fn read_texture(
+        &mut self,
+        texture: &Texture,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<TextureData, Error> {
+        self.read_wgpu_texture(&texture.wgpu, rect, mip_level)
+    }
+
+    pub(crate) fn read_wgpu_texture(
+        &mut self,
+        texture: &wgpu::Texture,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<TextureData, Error> {
+        let (mut request, submission_index) = self.submit_readback(texture, rect, mip_level)?;
+
+        self.poll_until(submission_index, || request.try_take())
+    }
+
+    /// Starts reading a region of `texture` without blocking. See
+    /// [`Renderer::read_texture`] for the meaning of the parameters.
+    ///
+    /// The returned [`ReadbackRequest`] can be awaited using any async
+    /// runtime, or checked each frame using [`ReadbackRequest::try_take`].
+    /// Either way, the device must be polled for the request to complete,
+    /// which [`Renderer::present`] and [`Device::poll`] do.
+    pub fn read_async(
+        &mut self,
+        texture: &Texture,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<ReadbackRequest, Error> {
+        let (request, _) = self.submit_readback(&texture.wgpu, rect, mip_level)?;
+        Ok(request)
+    }
 
-        let bytesize = 4 * fb.size();
+    fn submit_readback(
+        &mut self,
+        texture: &wgpu::Texture,
+        rect: Option<SizedRect<u32, ScreenSpace>>,
+        mip_level: u32,
+    ) -> Result<(ReadbackRequest, wgpu::SubmissionIndex), Error> {
+        let layout = ReadbackLayout::new(texture, rect, mip_level)?;
         let gpu_buffer = self.device.wgpu.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: bytesize as u64,
+            label: Some("easygpu readback buffer"),
+            size: layout.buffer_size(),
             usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
-        encoder.copy_texture_to_buffer(
-            wgpu::ImageCopyTexture {
-                texture: &fb.texture.wgpu,
-                mip_level: 0,
-                origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
-                aspect: TextureAspect::All,
-            },
-            wgpu::ImageCopyBuffer {
-                buffer: &gpu_buffer,
-                layout: wgpu::ImageDataLayout {
-                    offset: 0,
-                    // TODO: Must be a multiple of 256
-                    bytes_per_row: Some(4 * fb.texture.size.width),
-                    rows_per_image: Some(fb.texture.size.height),
-                },
-            },
-            fb.texture.extent,
-        );
+        let mut encoder = self.device.create_command_encoder();
+        layout.copy(texture, &gpu_buffer, &mut encoder);
         let submission_index = self.device.submit(vec![encoder.finish()]);
 
-        let mut buffer: Vec<u8> = Vec::with_capacity(bytesize);
+        Ok((ReadbackRequest::new(gpu_buffer, layout), submission_index))
+    }
 
-        let dst = gpu_buffer.slice(0..bytesize as u64);
+    /// Reads the elements of `buffer` within the byte range `range`. This
+    /// blocks until the GPU has finished writing to the buffer.
+    ///
+    /// The buffer must have been created with
+    /// [`wgpu::BufferUsages::COPY_SRC`], which buffers created by easygpu
+    /// have. The length of `range` must be a multiple of the size of `T`.
+    pub fn read_buffer<T: bytemuck::Pod>(
+        &mut self,
+        buffer: &wgpu::Buffer,
+        range: Range<wgpu::BufferAddress>,
+    ) -> Result<Vec<T>, Error> {
+        if !buffer.usage().contains(wgpu::BufferUsages::COPY_SRC) {
+            return Err(Error::BufferNotReadable);
+        }
+        let element_size = std::mem::size_of::<T>() as wgpu::BufferAddress;
+        if range.start > range.end
+            || range.end > buffer.size()
+            || (range.end - range.start) % element_size != 0
+        {
+            return Err(Error::InvalidBufferRange);
+        }
+        if range.start == range.end {
+            return Ok(Vec::new());
+        }
+
+        // Copies must be aligned to `COPY_BUFFER_ALIGNMENT`, so the copy is
+        // widened and the extra bytes are skipped when reading.
+        let alignment = wgpu::COPY_BUFFER_ALIGNMENT;
+        let start = range.start / alignment * alignment;
+        let end = range.end.div_ceil(alignment) * alignment;
+        if end > buffer.size() {
+            return Err(Error::InvalidBufferRange);
+        }
+        let size = end - start;
+
+        let staging = self.staging.take(&self.device.wgpu, size);
+        let mut encoder = self.device.create_command_encoder();
+        encoder.copy_buffer_to_buffer(buffer, start, &staging, 0, size);
+        let submission_index = self.device.submit(vec![encoder.finish()]);
+
+        let slice = staging.slice(0..size);
         let result = Arc::new(Mutex::new(None));
         let callback_result = result.clone();
-        dst.map_async(wgpu::MapMode::Read, move |map_result| {
-            let mut result = callback_result.lock().unwrap();
-            *result = Some(map_result);
+        slice.map_async(wgpu::MapMode::Read, move |map_result| {
+            *callback_result.lock().unwrap() = Some(map_result);
+        });
+        let mapped = self.poll_until(submission_index, || result.lock().unwrap().take());
+
+        let values = mapped.map(|_| {
+            let offset = (range.start - start) as usize;
+            let len = (range.end - range.start) as usize;
+            collect_pod(&slice.get_mapped_range()[offset..offset + len])
         });
+        if values.is_ok() {
+            staging.unmap();
+            self.staging.recycle(staging);
+        }
 
+        Ok(values?)
+    }
+
+    /// Polls the device until `f` returns a value. `f` must be able to return
+    /// a value once the submission at `submission_index` has completed.
+    fn poll_until<T>(
+        &self,
+        submission_index: wgpu::SubmissionIndex,
+        mut f: impl FnMut() -> Option<T>,
+    ) -> T {
         let mut queue_empty = self
             .device
             .wgpu
             .poll(wgpu::MaintainBase::WaitForSubmissionIndex(submission_index));
         loop {
-            let result = result.lock().unwrap().take();
-            match result {
-                Some(Ok(())) => break,
-                Some(Err(err)) => return Err(err),
+            match f() {
+                Some(value) => return value,
                 None => {
                     assert!(!queue_empty);
 
                     // We didn't get our map callback, but the submission is done.
                     // We'll keep polling the device until we get our map callback.
-                    queue_empty = self.device.wgpu.poll(wgpu::MaintainBase::Poll);
+                    queue_empty = self.device.poll();
                 }
             }
         }
-
-        let view = dst.get_mapped_range();
-        buffer.extend_from_slice(&view);
-        if buffer.len() == bytesize {
-            let (head, body, tail) = unsafe { buffer.align_to::<Bgra8>() };
-            if !(head.is_empty() && tail.is_empty()) {
-                panic!("Renderer::read: framebuffer is not a valid Bgra8 buffer");
-            }
-            f(body);
-        }
-
-        gpu_buffer.unmap();
-
-        Ok(())
     }
 
     pub fn update_pipeline<'a, T>(&mut self, pip: &'a T, p: T::PrepareContext)
@@ -257,15 +552,46 @@ impl Renderer {
         self.device.submit(vec![frame.encoder.finish()]);
     }
 
-    pub fn submit<T: Copy>(&mut self, commands: &[Op<T>]) {
+    /// Encodes and submits `commands`, which are executed in order. If a
+    /// command fails, the commands before it are still executed and the
+    /// remaining commands are not.
+    ///
+    /// [`Op::Clear`], [`Op::Fill`] and [`Op::Transfer`] are written by the
+    /// queue, which executes them before any command buffer submitted after
+    /// them. The blits preceding each of them are submitted first.
+    pub fn submit<T: Copy>(&mut self, commands: &[Op<T>]) -> Result<(), Error> {
         let mut encoder = self.device.create_command_encoder();
+        let mut pending_blits = false;
+        let mut result = Ok(());
         for c in commands.iter() {
-            c.encode(&mut self.device, &mut encoder);
+            let is_blit = matches!(c, Op::Blit(..));
+            if pending_blits && !is_blit {
+                let blits = mem::replace(&mut encoder, self.device.create_command_encoder());
+                self.device.submit(vec![blits.finish()]);
+                pending_blits = false;
+            }
+            if let Err(err) = c.encode(&mut self.device, &mut encoder) {
+                result = Err(err);
+                break;
+            }
+            pending_blits |= is_blit;
+        }
+        if pending_blits {
+            self.device.submit(vec![encoder.finish()]);
         }
-        self.device.submit(vec![encoder.finish()]);
+        result
     }
 }
 
+/// Returns the labels for the vertex and fragment shaders of a pipeline.
+fn shader_labels(desc: &PipelineDescription<'_>) -> (String, String) {
+    let label = desc.label.unwrap_or("easygpu pipeline");
+    (
+        format!("{} vertex shader", label),
+        format!("{} fragment shader", label),
+    )
+}
+
 pub enum Op<'a, T> {
     Clear(&'a dyn Canvas<Color = T>, T),
     Fill(&'a dyn Canvas<Color = T>, &'a [T]),
@@ -285,19 +611,14 @@ impl<'a, T> Op<'a, T>
 where
     T: Copy,
 {
-    fn encode(&self, dev: &mut Device, encoder: &mut wgpu::CommandEncoder) {
+    fn encode(&self, dev: &mut Device, encoder: &mut wgpu::CommandEncoder) -> Result<(), Error> {
         match *self {
-            Op::Clear(f, color) => {
-                f.clear(color, dev, encoder);
-            }
-            Op::Fill(f, buf) => {
-                f.fill(buf, dev, encoder);
-            }
-            Op::Transfer { f, buf, rect } => {
-                f.transfer(buf, rect, dev, encoder);
-            }
+            Op::Clear(f, color) => f.clear(color, dev),
+            Op::Fill(f, buf) => f.fill(buf, dev),
+            Op::Transfer { f, buf, rect } => f.transfer(buf, rect, dev),
             Op::Blit(f, src, dst) => {
                 f.blit(src, dst, encoder);
+                Ok(())
             }
         }
     }
@@ -318,11 +639,11 @@ pub trait RenderPassExt<'a> {
 
     fn set_binding(&mut self, group: &'a BindingGroup, offsets: &[u32]);
 
-    fn set_easy_index_buffer(&mut self, index_buf: &'a IndexBuffer);
-    fn set_easy_vertex_buffer(&mut self, vertex_buf: &'a VertexBuffer);
+    fn set_easy_index_buffer<I: IndexType>(&mut self, index_buf: &'a IndexBuffer<I>);
+    fn set_easy_vertex_buffer<T: bytemuck::Pod>(&mut self, vertex_buf: &'a VertexBuffer<T>);
     fn easy_draw<T: Draw>(&mut self, drawable: &'a T, binding: &'a BindingGroup);
-    fn draw_buffer(&mut self, buf: &'a VertexBuffer);
-    fn draw_buffer_range(&mut self, buf: &'a VertexBuffer, range: Range<u32>);
+    fn draw_buffer<T: bytemuck::Pod>(&mut self, buf: &'a VertexBuffer<T>);
+    fn draw_buffer_range<T: bytemuck::Pod>(&mut self, buf: &'a VertexBuffer<T>, range: Range<u32>);
     fn draw_indexed(&mut self, indices: Range<u32>, instances: Range<u32>);
 }
 
@@ -335,7 +656,7 @@ impl<'a> RenderPassExt<'a> for wgpu::RenderPass<'a> {
         op: PassOp,
     ) -> Self {
         encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: None,
+            label: Some("easygpu render pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view,
                 resolve_target,
@@ -370,11 +691,11 @@ impl<'a> RenderPassExt<'a> for wgpu::RenderPass<'a> {
         self.set_bind_group(group.set_index, &group.wgpu, offsets);
     }
 
-    fn set_easy_index_buffer(&mut self, index_buf: &'a IndexBuffer) {
-        self.set_index_buffer(index_buf.slice(), wgpu::IndexFormat::Uint16)
+    fn set_easy_index_buffer<I: IndexType>(&mut self, index_buf: &'a IndexBuffer<I>) {
+        self.set_index_buffer(index_buf.slice(), I::FORMAT)
     }
 
-    fn set_easy_vertex_buffer(&mut self, vertex_buf: &'a VertexBuffer) {
+    fn set_easy_vertex_buffer<T: bytemuck::Pod>(&mut self, vertex_buf: &'a VertexBuffer<T>) {
         self.set_vertex_buffer(0, vertex_buf.slice())
     }
 
@@ -382,12 +703,12 @@ impl<'a> RenderPassExt<'a> for wgpu::RenderPass<'a> {
         drawable.draw(binding, self);
     }
 
-    fn draw_buffer(&mut self, buf: &'a VertexBuffer) {
+    fn draw_buffer<T: bytemuck::Pod>(&mut self, buf: &'a VertexBuffer<T>) {
         self.set_easy_vertex_buffer(buf);
-        self.draw(0..buf.size, 0..1);
+        self.draw(0..buf.count, 0..1);
     }
 
-    fn draw_buffer_range(&mut self, buf: &'a VertexBuffer, range: Range<u32>) {
+    fn draw_buffer_range<T: bytemuck::Pod>(&mut self, buf: &'a VertexBuffer<T>, range: Range<u32>) {
         self.set_easy_vertex_buffer(buf);
         self.draw(range, 0..1);
     }
@@ -418,13 +739,24 @@ pub trait RenderTarget {
     fn color_target(&self) -> &wgpu::TextureView;
     /// Depth component.
     fn zdepth_target(&self) -> &wgpu::TextureView;
+    /// Multisampled color component, resolved to the color component when
+    /// using [`Frame::resolving_pass`].
+    fn multisample_target(&self) -> Option<&wgpu::TextureView> {
+        None
+    }
 }
 
 pub struct RenderFrame {
     pub view: wgpu::TextureView,
     pub wgpu: Option<wgpu::SurfaceTexture>,
-    pub depth: DepthBuffer,
+    /// The renderer's depth buffer, which is shared between frames.
+    pub depth: Arc<DepthBuffer>,
+    /// The renderer's multisampled color target, which is shared between
+    /// frames. `None` if the renderer's sample count is 1.
+    pub multisample: Option<Arc<Texture>>,
     pub size: Size<u32, Pixels>,
+    /// The alpha mode the surface was configured with.
+    pub alpha_mode: wgpu::CompositeAlphaMode,
 }
 
 impl RenderTarget for RenderFrame {
@@ -435,6 +767,10 @@ impl RenderTarget for RenderFrame {
     fn zdepth_target(&self) -> &wgpu::TextureView {
         &self.depth.texture.view
     }
+
+    fn multisample_target(&self) -> Option<&wgpu::TextureView> {
+        self.multisample.as_ref().map(|texture| &texture.view)
+    }
 }
 
 impl Drop for RenderFrame {
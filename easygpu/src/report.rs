@@ -0,0 +1,337 @@
+use std::fmt::{self, Display, Write};
+
+use wgpu::{AstcBlock, AstcChannel, TextureFormat};
+
+use crate::device::Device;
+
+/// A summary of the adapter and device capabilities, intended to be included
+/// in logs and bug reports.
+///
+/// The [`Display`] implementation produces a plain-text report, and
+/// `AdapterReport::to_json` produces a JSON document when the `json` feature
+/// is enabled.
+#[derive(Debug, Clone)]
+pub struct AdapterReport {
+    pub info: wgpu::AdapterInfo,
+    /// The features enabled on the device.
+    pub features: wgpu::Features,
+    /// The limits of the device.
+    pub limits: wgpu::Limits,
+    pub downlevel: wgpu::DownlevelCapabilities,
+    /// The capabilities of the device's surface, if it has one.
+    pub surface: Option<SurfaceReport>,
+    /// The capabilities of every texture format supported by the adapter.
+    pub formats: Vec<FormatReport>,
+}
+
+/// The capabilities of a surface when used with an adapter.
+#[derive(Debug, Clone)]
+pub struct SurfaceReport {
+    pub formats: Vec<TextureFormat>,
+    pub present_modes: Vec<wgpu::PresentMode>,
+    pub alpha_modes: Vec<wgpu::CompositeAlphaMode>,
+}
+
+/// The capabilities of a texture format on an adapter.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatReport {
+    pub format: TextureFormat,
+    pub features: wgpu::TextureFormatFeatures,
+}
+
+impl AdapterReport {
+    pub fn new(device: &Device) -> Self {
+        let adapter = &device.adapter;
+        let adapter_features = adapter.features();
+        let surface = device.surface.as_ref().map(|surface| {
+            let capabilities = surface.capabilities(adapter);
+            SurfaceReport {
+                formats: capabilities.formats,
+                present_modes: capabilities.present_modes,
+                alpha_modes: capabilities.alpha_modes,
+            }
+        });
+        let formats = all_texture_formats()
+            .filter(|format| adapter_features.contains(format.required_features()))
+            .map(|format| FormatReport {
+                format,
+                features: adapter.get_texture_format_features(format),
+            })
+            .collect();
+
+        Self {
+            info: adapter.get_info(),
+            features: device.features(),
+            limits: device.limits(),
+            downlevel: adapter.get_downlevel_capabilities(),
+            surface,
+            formats,
+        }
+    }
+
+    /// Returns this report as a JSON document.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        let limits = limit_entries(&self.limits)
+            .iter()
+            .map(|(name, value)| (name.to_string(), serde_json::Value::from(*value)))
+            .collect::<serde_json::Map<_, _>>();
+        let surface = self.surface.as_ref().map(|surface| {
+            serde_json::json!({
+                "formats": debug_names(&surface.formats),
+                "present_modes": debug_names(&surface.present_modes),
+                "alpha_modes": debug_names(&surface.alpha_modes),
+            })
+        });
+        let formats = self
+            .formats
+            .iter()
+            .map(|format| {
+                serde_json::json!({
+                    "format": format!("{:?}", format.format),
+                    "allowed_usages": flag_names(format.features.allowed_usages.iter_names()),
+                    "flags": flag_names(format.features.flags.iter_names()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "adapter": {
+                "name": self.info.name,
+                "vendor": self.info.vendor,
+                "device": self.info.device,
+                "device_type": format!("{:?}", self.info.device_type),
+                "driver": self.info.driver,
+                "driver_info": self.info.driver_info,
+                "backend": format!("{:?}", self.info.backend),
+            },
+            "features": flag_names(self.features.iter_names()),
+            "limits": limits,
+            "downlevel": {
+                "flags": flag_names(self.downlevel.flags.iter_names()),
+                "shader_model": format!("{:?}", self.downlevel.shader_model),
+            },
+            "surface": surface,
+            "formats": formats,
+        })
+        .to_string()
+    }
+}
+
+impl Display for AdapterReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Adapter: {} ({:?}, {:?})",
+            self.info.name, self.info.device_type, self.info.backend
+        )?;
+        writeln!(
+            f,
+            "  vendor: {:#06x}, device: {:#06x}",
+            self.info.vendor, self.info.device
+        )?;
+        writeln!(
+            f,
+            "  driver: {} ({})",
+            self.info.driver, self.info.driver_info
+        )?;
+        writeln!(
+            f,
+            "Features: {}",
+            flag_names(self.features.iter_names()).join(", ")
+        )?;
+        writeln!(f, "Limits:")?;
+        for (name, value) in limit_entries(&self.limits) {
+            writeln!(f, "  {name}: {value}")?;
+        }
+        writeln!(
+            f,
+            "Downlevel: {} ({:?})",
+            flag_names(self.downlevel.flags.iter_names()).join(", "),
+            self.downlevel.shader_model
+        )?;
+        if let Some(surface) = &self.surface {
+            writeln!(f, "Surface:")?;
+            writeln!(f, "  formats: {}", debug_names(&surface.formats).join(", "))?;
+            writeln!(
+                f,
+                "  present modes: {}",
+                debug_names(&surface.present_modes).join(", ")
+            )?;
+            writeln!(
+                f,
+                "  alpha modes: {}",
+                debug_names(&surface.alpha_modes).join(", ")
+            )?;
+        }
+        writeln!(f, "Texture formats:")?;
+        for format in &self.formats {
+            let mut line = String::new();
+            write!(
+                line,
+                "  {:?}: {}",
+                format.format,
+                flag_names(format.features.allowed_usages.iter_names()).join(" | ")
+            )?;
+            let flags = flag_names(format.features.flags.iter_names());
+            if !flags.is_empty() {
+                write!(line, " ({})", flags.join(" | "))?;
+            }
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+fn flag_names<T>(names: impl Iterator<Item = (&'static str, T)>) -> Vec<&'static str> {
+    names.map(|(name, _)| name).collect()
+}
+
+fn debug_names<T: fmt::Debug>(values: &[T]) -> Vec<String> {
+    values.iter().map(|value| format!("{value:?}")).collect()
+}
+
+macro_rules! limit_entries {
+    ($limits:ident, [$($name:ident),+ $(,)?]) => {
+        vec![$((stringify!($name), u64::from($limits.$name))),+]
+    };
+}
+
+fn limit_entries(limits: &wgpu::Limits) -> Vec<(&'static str, u64)> {
+    limit_entries!(
+        limits,
+        [
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_bindings_per_bind_group,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_buffer_size,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment,
+            max_inter_stage_shader_components,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension,
+            max_push_constant_size,
+        ]
+    )
+}
+
+const FORMATS: &[TextureFormat] = &[
+    TextureFormat::R8Unorm,
+    TextureFormat::R8Snorm,
+    TextureFormat::R8Uint,
+    TextureFormat::R8Sint,
+    TextureFormat::R16Uint,
+    TextureFormat::R16Sint,
+    TextureFormat::R16Unorm,
+    TextureFormat::R16Snorm,
+    TextureFormat::R16Float,
+    TextureFormat::Rg8Unorm,
+    TextureFormat::Rg8Snorm,
+    TextureFormat::Rg8Uint,
+    TextureFormat::Rg8Sint,
+    TextureFormat::R32Uint,
+    TextureFormat::R32Sint,
+    TextureFormat::R32Float,
+    TextureFormat::Rg16Uint,
+    TextureFormat::Rg16Sint,
+    TextureFormat::Rg16Unorm,
+    TextureFormat::Rg16Snorm,
+    TextureFormat::Rg16Float,
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::Rgba8Snorm,
+    TextureFormat::Rgba8Uint,
+    TextureFormat::Rgba8Sint,
+    TextureFormat::Bgra8Unorm,
+    TextureFormat::Bgra8UnormSrgb,
+    TextureFormat::Rgb9e5Ufloat,
+    TextureFormat::Rgb10a2Unorm,
+    TextureFormat::Rg11b10Float,
+    TextureFormat::Rg32Uint,
+    TextureFormat::Rg32Sint,
+    TextureFormat::Rg32Float,
+    TextureFormat::Rgba16Uint,
+    TextureFormat::Rgba16Sint,
+    TextureFormat::Rgba16Unorm,
+    TextureFormat::Rgba16Snorm,
+    TextureFormat::Rgba16Float,
+    TextureFormat::Rgba32Uint,
+    TextureFormat::Rgba32Sint,
+    TextureFormat::Rgba32Float,
+    TextureFormat::Stencil8,
+    TextureFormat::Depth16Unorm,
+    TextureFormat::Depth24Plus,
+    TextureFormat::Depth24PlusStencil8,
+    TextureFormat::Depth32Float,
+    TextureFormat::Depth32FloatStencil8,
+    TextureFormat::Bc1RgbaUnorm,
+    TextureFormat::Bc1RgbaUnormSrgb,
+    TextureFormat::Bc2RgbaUnorm,
+    TextureFormat::Bc2RgbaUnormSrgb,
+    TextureFormat::Bc3RgbaUnorm,
+    TextureFormat::Bc3RgbaUnormSrgb,
+    TextureFormat::Bc4RUnorm,
+    TextureFormat::Bc4RSnorm,
+    TextureFormat::Bc5RgUnorm,
+    TextureFormat::Bc5RgSnorm,
+    TextureFormat::Bc6hRgbUfloat,
+    TextureFormat::Bc6hRgbFloat,
+    TextureFormat::Bc7RgbaUnorm,
+    TextureFormat::Bc7RgbaUnormSrgb,
+    TextureFormat::Etc2Rgb8Unorm,
+    TextureFormat::Etc2Rgb8UnormSrgb,
+    TextureFormat::Etc2Rgb8A1Unorm,
+    TextureFormat::Etc2Rgb8A1UnormSrgb,
+    TextureFormat::Etc2Rgba8Unorm,
+    TextureFormat::Etc2Rgba8UnormSrgb,
+    TextureFormat::EacR11Unorm,
+    TextureFormat::EacR11Snorm,
+    TextureFormat::EacRg11Unorm,
+    TextureFormat::EacRg11Snorm,
+];
+
+const ASTC_BLOCKS: &[AstcBlock] = &[
+    AstcBlock::B4x4,
+    AstcBlock::B5x4,
+    AstcBlock::B5x5,
+    AstcBlock::B6x5,
+    AstcBlock::B6x6,
+    AstcBlock::B8x5,
+    AstcBlock::B8x6,
+    AstcBlock::B8x8,
+    AstcBlock::B10x5,
+    AstcBlock::B10x6,
+    AstcBlock::B10x8,
+    AstcBlock::B10x10,
+    AstcBlock::B12x10,
+    AstcBlock::B12x12,
+];
+
+/// Returns every texture format known to easygpu.
+pub fn all_texture_formats() -> impl Iterator<Item = TextureFormat> {
+    let astc = ASTC_BLOCKS.iter().flat_map(|&block| {
+        [AstcChannel::Unorm, AstcChannel::UnormSrgb, AstcChannel::Hdr]
+            .iter()
+            .map(move |&channel| TextureFormat::Astc { block, channel })
+    });
+    FORMATS.iter().copied().chain(astc)
+}
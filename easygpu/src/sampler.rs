@@ -1,15 +1,266 @@
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use wgpu::{AddressMode, CompareFunction, FilterMode, SamplerBorderColor};
+
 use crate::binding::Bind;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Sampler {
-    pub wgpu: wgpu::Sampler,
+    /// The sampler, which may be shared with other samplers created from the
+    /// same [`SamplerDescriptor`].
+    pub wgpu: Arc<wgpu::Sampler>,
+    pub descriptor: SamplerDescriptor,
 }
 
 impl Bind for Sampler {
-    fn binding(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::Sampler(&self.wgpu),
         }
     }
 }
+
+/// Describes how a [`Sampler`] samples textures. Samplers created from equal
+/// descriptors using
+/// [`Device::create_sampler_with_descriptor`](crate::device::Device::create_sampler_with_descriptor)
+/// share one [`wgpu::Sampler`].
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerDescriptor {
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub address_mode_w: AddressMode,
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    /// How the sampler filters between mip levels.
+    pub mipmap_filter: FilterMode,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    /// The comparison function of a comparison sampler, such as one used to
+    /// sample shadow maps.
+    pub compare: Option<CompareFunction>,
+    /// The maximum anisotropy. Values above 1 require all filters to be
+    /// [`FilterMode::Linear`].
+    pub anisotropy_clamp: u16,
+    /// The color used by [`AddressMode::ClampToBorder`].
+    pub border_color: Option<SamplerBorderColor>,
+}
+
+impl Default for SamplerDescriptor {
+    fn default() -> Self {
+        Self {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            lod_min_clamp: 0.,
+            lod_max_clamp: 100.,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color: None,
+        }
+    }
+}
+
+impl SamplerDescriptor {
+    /// Describes a sampler using `filter` for minification, magnification and
+    /// filtering between mip levels, which clamps coordinates to the edge of
+    /// the texture.
+    pub fn new(filter: FilterMode) -> Self {
+        Self::default().with_filter(filter)
+    }
+
+    /// Sets the minification, magnification and mip level filters.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.min_filter = filter;
+        self.mag_filter = filter;
+        self.mipmap_filter = filter;
+        self
+    }
+
+    pub fn with_min_filter(mut self, filter: FilterMode) -> Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn with_mag_filter(mut self, filter: FilterMode) -> Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn with_mipmap_filter(mut self, filter: FilterMode) -> Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    /// Sets the address mode of all three texture coordinates.
+    pub fn with_address_mode(self, mode: AddressMode) -> Self {
+        self.with_address_modes(mode, mode, mode)
+    }
+
+    pub fn with_address_modes(mut self, u: AddressMode, v: AddressMode, w: AddressMode) -> Self {
+        self.address_mode_u = u;
+        self.address_mode_v = v;
+        self.address_mode_w = w;
+        self
+    }
+
+    /// Clamps all three texture coordinates to a border of `color`. Requires
+    /// [`wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER`].
+    pub fn with_border_color(mut self, color: SamplerBorderColor) -> Self {
+        self.border_color = Some(color);
+        self.with_address_mode(AddressMode::ClampToBorder)
+    }
+
+    pub fn with_lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.lod_min_clamp = min;
+        self.lod_max_clamp = max;
+        self
+    }
+
+    /// Enables anisotropic filtering of up to `clamp` samples, which also
+    /// sets all filters to [`FilterMode::Linear`] as wgpu requires.
+    pub fn with_anisotropy(mut self, clamp: u16) -> Self {
+        self.anisotropy_clamp = clamp;
+        self.with_filter(FilterMode::Linear)
+    }
+
+    /// Makes this a comparison sampler using `compare`.
+    pub fn with_compare(mut self, compare: CompareFunction) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    pub fn to_wgpu<'a>(&self, label: Option<&'a str>) -> wgpu::SamplerDescriptor<'a> {
+        wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            address_mode_w: self.address_mode_w,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            compare: self.compare,
+            anisotropy_clamp: self.anisotropy_clamp,
+            border_color: self.border_color,
+        }
+    }
+
+    /// The fields compared and hashed by the sampler cache, with the LOD
+    /// clamps compared by their bits.
+    #[allow(clippy::type_complexity)]
+    fn key(
+        &self,
+    ) -> (
+        [AddressMode; 3],
+        [FilterMode; 3],
+        [u32; 2],
+        Option<CompareFunction>,
+        u16,
+        Option<SamplerBorderColor>,
+    ) {
+        (
+            [
+                self.address_mode_u,
+                self.address_mode_v,
+                self.address_mode_w,
+            ],
+            [self.mag_filter, self.min_filter, self.mipmap_filter],
+            [self.lod_min_clamp.to_bits(), self.lod_max_clamp.to_bits()],
+            self.compare,
+            self.anisotropy_clamp,
+            self.border_color,
+        )
+    }
+}
+
+impl PartialEq for SamplerDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SamplerDescriptor {}
+
+impl Hash for SamplerDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use wgpu::{AddressMode, CompareFunction, FilterMode, SamplerBorderColor};
+
+    use super::SamplerDescriptor;
+
+    fn hash(descriptor: &SamplerDescriptor) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        descriptor.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn equal_descriptors_hash_the_same() {
+        let descriptor = SamplerDescriptor::new(FilterMode::Linear)
+            .with_address_mode(AddressMode::Repeat)
+            .with_lod_clamp(0.5, 4.)
+            .with_compare(CompareFunction::Less);
+        let copy = SamplerDescriptor::new(FilterMode::Linear)
+            .with_address_mode(AddressMode::Repeat)
+            .with_lod_clamp(0.5, 4.)
+            .with_compare(CompareFunction::Less);
+
+        assert_eq!(descriptor, copy);
+        assert_eq!(hash(&descriptor), hash(&copy));
+    }
+
+    #[test]
+    fn every_field_is_part_of_the_key() {
+        let base = SamplerDescriptor::default();
+        let changed = [
+            SamplerDescriptor {
+                address_mode_u: AddressMode::Repeat,
+                ..base
+            },
+            SamplerDescriptor {
+                address_mode_v: AddressMode::Repeat,
+                ..base
+            },
+            SamplerDescriptor {
+                address_mode_w: AddressMode::Repeat,
+                ..base
+            },
+            base.with_mag_filter(FilterMode::Linear),
+            base.with_min_filter(FilterMode::Linear),
+            base.with_mipmap_filter(FilterMode::Linear),
+            base.with_lod_clamp(1., 100.),
+            base.with_lod_clamp(0., 8.),
+            base.with_compare(CompareFunction::LessEqual),
+            SamplerDescriptor {
+                anisotropy_clamp: 16,
+                ..base
+            },
+            SamplerDescriptor {
+                border_color: Some(SamplerBorderColor::OpaqueWhite),
+                ..base
+            },
+        ];
+
+        for (index, descriptor) in changed.iter().enumerate() {
+            assert_ne!(&base, descriptor, "descriptor {}", index);
+            assert_ne!(hash(&base), hash(descriptor), "descriptor {}", index);
+            for other in &changed[index + 1..] {
+                assert_ne!(descriptor, other);
+            }
+        }
+    }
+}
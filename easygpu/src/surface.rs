@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use figures::{Pixels, Size};
+use wgpu::{CompositeAlphaMode, PresentMode, TextureFormat, TextureUsages, TextureViewDescriptor};
+
+use crate::buffers::DepthBuffer;
+use crate::device::Device;
+use crate::error::Error;
+use crate::renderer::RenderFrame;
+use crate::texture::Texture;
+use crate::transform::ScreenSpace;
+
+/// Options used when configuring a surface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurfaceOptions {
+    pub format: TextureFormat,
+    pub present_mode: PresentMode,
+    /// How the surface's alpha channel is composited with the rest of the
+    /// desktop. Use [`CompositeAlphaMode::PreMultiplied`] or
+    /// [`CompositeAlphaMode::PostMultiplied`] for transparent windows.
+    pub alpha_mode: CompositeAlphaMode,
+    /// Additional formats that views of the surface's textures may use. Only
+    /// the sRGB-ness of a view format may differ from `format`.
+    pub view_formats: Vec<TextureFormat>,
+    pub usage: TextureUsages,
+}
+
+impl SurfaceOptions {
+    pub fn new(format: TextureFormat) -> Self {
+        Self {
+            format,
+            present_mode: PresentMode::Fifo,
+            alpha_mode: CompositeAlphaMode::Auto,
+            view_formats: Vec::new(),
+            usage: TextureUsages::RENDER_ATTACHMENT,
+        }
+    }
+
+    /// Picks the first of `formats` and `present_modes` that `capabilities`
+    /// supports. If none of the preferred formats are supported, the surface's
+    /// own preferred format is used. If none of the preferred present modes
+    /// are supported, [`PresentMode::Fifo`] is used, which is always
+    /// supported.
+    ///
+    /// Returns `None` if the surface doesn't support any formats, which
+    /// happens when it is incompatible with the adapter.
+    pub fn negotiate(
+        capabilities: &wgpu::SurfaceCapabilities,
+        formats: &[TextureFormat],
+        present_modes: &[PresentMode],
+    ) -> Option<Self> {
+        let format = formats
+            .iter()
+            .find(|format| capabilities.formats.contains(format))
+            .or_else(|| capabilities.formats.first())?;
+        let present_mode = present_modes
+            .iter()
+            .find(|mode| capabilities.present_modes.contains(mode))
+            .copied()
+            .unwrap_or(PresentMode::Fifo);
+
+        Some(Self::new(*format).with_present_mode(present_mode))
+    }
+
+    pub fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: CompositeAlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    pub fn with_view_formats(
+        mut self,
+        view_formats: impl IntoIterator<Item = TextureFormat>,
+    ) -> Self {
+        self.view_formats.extend(view_formats);
+        self
+    }
+
+    pub fn with_usage(mut self, usage: TextureUsages) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Picks the first of `alpha_modes` that `capabilities` supports, keeping
+    /// the current alpha mode if none are supported.
+    pub fn with_preferred_alpha_mode(
+        mut self,
+        capabilities: &wgpu::SurfaceCapabilities,
+        alpha_modes: &[CompositeAlphaMode],
+    ) -> Self {
+        if let Some(alpha_mode) = alpha_modes
+            .iter()
+            .find(|mode| capabilities.alpha_modes.contains(mode))
+        {
+            self.alpha_mode = *alpha_mode;
+        }
+        self
+    }
+}
+
+/// A surface that can be rendered to, along with its configuration and the
+/// buffers sized to it.
+///
+/// A [`Device`] can render to any number of surfaces, such as one per window.
+/// Additional surfaces are created using
+/// [`Renderer::add_surface`](crate::renderer::Renderer::add_surface).
+#[derive(Debug)]
+pub struct SurfaceTarget {
+    pub surface: wgpu::Surface,
+    config: Option<wgpu::SurfaceConfiguration>,
+    size: Size<u32, Pixels>,
+    /// The depth buffer used by every [`RenderFrame`] of this surface.
+    depth: Option<Arc<DepthBuffer>>,
+    /// The multisampled color target used by every [`RenderFrame`] of this
+    /// surface when the sample count is greater than 1.
+    multisample: Option<Arc<Texture>>,
+}
+
+impl SurfaceTarget {
+    pub(crate) fn new(surface: wgpu::Surface) -> Self {
+        Self {
+            surface,
+            config: None,
+            size: Size::default(),
+            depth: None,
+            multisample: None,
+        }
+    }
+
+    /// Returns the size the surface was last configured with.
+    pub const fn size(&self) -> Size<u32, Pixels> {
+        self.size
+    }
+
+    /// Returns the configuration the surface was last configured with.
+    pub const fn configuration(&self) -> Option<&wgpu::SurfaceConfiguration> {
+        self.config.as_ref()
+    }
+
+    /// Returns the capabilities of the surface when used with `adapter`.
+    pub fn capabilities(&self, adapter: &wgpu::Adapter) -> wgpu::SurfaceCapabilities {
+        self.surface.get_capabilities(adapter)
+    }
+
+    pub(crate) fn configure(
+        &mut self,
+        device: &wgpu::Device,
+        size: Size<u32, ScreenSpace>,
+        options: &SurfaceOptions,
+    ) {
+        let desc = wgpu::SurfaceConfiguration {
+            usage: options.usage,
+            format: options.format,
+            present_mode: options.present_mode,
+            width: size.width,
+            height: size.height,
+            alpha_mode: options.alpha_mode,
+            view_formats: options.view_formats.clone(),
+        };
+        self.surface.configure(device, &desc);
+        self.size = size;
+        self.config = Some(desc);
+    }
+
+    pub(crate) fn reconfigure(&self, device: &wgpu::Device) -> bool {
+        match &self.config {
+            Some(config) => {
+                self.surface.configure(device, config);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Recreates the buffers that are sized to the surface if the surface's
+    /// size, its format or the sample count has changed.
+    pub(crate) fn update_buffers(&mut self, device: &Device, sample_count: u32) {
+        let size = self.size;
+        let depth_is_current = self.depth.as_ref().is_some_and(|depth| {
+            depth.texture.size == size && depth.texture.wgpu.sample_count() == sample_count
+        });
+        if !depth_is_current {
+            self.depth = Some(Arc::new(device.create_zbuffer(
+                size,
+                sample_count,
+                Some("easygpu frame depth buffer"),
+            )));
+        }
+
+        let format = match &self.config {
+            Some(config) if sample_count > 1 => config.format,
+            _ => {
+                self.multisample = None;
+                return;
+            }
+        };
+        let multisample_is_current = self.multisample.as_ref().is_some_and(|texture| {
+            texture.size == size
+                && texture.format == format
+                && texture.wgpu.sample_count() == sample_count
+        });
+        if !multisample_is_current {
+            self.multisample = Some(Arc::new(device.create_texture(
+                size,
+                format,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+                sample_count,
+                Some("easygpu frame multisample texture"),
+            )));
+        }
+    }
+
+    /// Returns the next frame of the surface. See
+    /// [`Renderer::current_frame`](crate::renderer::Renderer::current_frame).
+    pub(crate) fn current_frame(
+        &self,
+        device: &wgpu::Device,
+    ) -> Result<Option<RenderFrame>, Error> {
+        let (config, depth) = match (&self.config, &self.depth) {
+            (Some(config), Some(depth)) => (config, depth.clone()),
+            _ => return Err(Error::SurfaceNotConfigured),
+        };
+        let surface_texture = match self.surface.get_current_texture() {
+            Ok(surface_texture) => surface_texture,
+            Err(wgpu::SurfaceError::Timeout) => return Ok(None),
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                if self.size.width == 0 || self.size.height == 0 {
+                    return Ok(None);
+                }
+                self.reconfigure(device);
+                match self.surface.get_current_texture() {
+                    Ok(surface_texture) => surface_texture,
+                    Err(wgpu::SurfaceError::Timeout) => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let view = surface_texture
+            .texture
+            .create_view(&TextureViewDescriptor::default());
+        Ok(Some(RenderFrame {
+            wgpu: Some(surface_texture),
+            view,
+            depth,
+            multisample: self.multisample.clone(),
+            size: self.size,
+            alpha_mode: config.alpha_mode,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{CompositeAlphaMode, PresentMode, SurfaceCapabilities, TextureFormat};
+
+    use super::SurfaceOptions;
+
+    fn capabilities(
+        formats: &[TextureFormat],
+        present_modes: &[PresentMode],
+    ) -> SurfaceCapabilities {
+        SurfaceCapabilities {
+            formats: formats.to_vec(),
+            present_modes: present_modes.to_vec(),
+            alpha_modes: vec![CompositeAlphaMode::Opaque],
+        }
+    }
+
+    #[test]
+    fn empty_preferences_use_the_surface_defaults() {
+        let caps = capabilities(
+            &[TextureFormat::Bgra8UnormSrgb, TextureFormat::Bgra8Unorm],
+            &[PresentMode::Fifo, PresentMode::Mailbox],
+        );
+        let options = SurfaceOptions::negotiate(&caps, &[], &[]).unwrap();
+        assert_eq!(options.format, TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(options.present_mode, PresentMode::Fifo);
+    }
+
+    #[test]
+    fn preferences_are_tried_in_order() {
+        let caps = capabilities(
+            &[TextureFormat::Bgra8Unorm, TextureFormat::Bgra8UnormSrgb],
+            &[
+                PresentMode::Fifo,
+                PresentMode::Immediate,
+                PresentMode::Mailbox,
+            ],
+        );
+        let options = SurfaceOptions::negotiate(
+            &caps,
+            &[TextureFormat::Rgba8UnormSrgb, TextureFormat::Bgra8UnormSrgb],
+            &[PresentMode::Mailbox, PresentMode::Immediate],
+        )
+        .unwrap();
+        assert_eq!(options.format, TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(options.present_mode, PresentMode::Mailbox);
+    }
+
+    #[test]
+    fn srgb_preference_falls_back_to_a_linear_surface() {
+        let caps = capabilities(
+            &[TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Unorm],
+            &[PresentMode::Fifo],
+        );
+        let options =
+            SurfaceOptions::negotiate(&caps, &[TextureFormat::Bgra8UnormSrgb], &[]).unwrap();
+        assert_eq!(options.format, TextureFormat::Rgba8Unorm);
+    }
+
+    #[test]
+    fn no_overlap_falls_back_to_fifo_and_the_preferred_format() {
+        let caps = capabilities(&[TextureFormat::Rgba16Float], &[PresentMode::Fifo]);
+        let options = SurfaceOptions::negotiate(
+            &caps,
+            &[TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba8UnormSrgb],
+            &[PresentMode::Mailbox, PresentMode::Immediate],
+        )
+        .unwrap();
+        assert_eq!(options.format, TextureFormat::Rgba16Float);
+        assert_eq!(options.present_mode, PresentMode::Fifo);
+    }
+
+    #[test]
+    fn incompatible_surfaces_negotiate_nothing() {
+        let caps = capabilities(&[], &[]);
+        assert_eq!(
+            SurfaceOptions::negotiate(&caps, &[TextureFormat::Bgra8UnormSrgb], &[]),
+            None
+        );
+    }
+}
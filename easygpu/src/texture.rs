@@ -1,11 +1,17 @@
+use std::sync::OnceLock;
+
 use figures::{ExtentsRect, Point, Rectlike, Size, SizedRect};
-use wgpu::TextureAspect;
+use wgpu::{TextureAspect, TextureFormat, TextureUsages, TextureViewDimension};
 
 use crate::binding::Bind;
-use crate::buffers::Framebuffer;
+use crate::buffers::{DepthBuffer, Framebuffer};
 use crate::canvas::Canvas;
 use crate::color::Rgba8;
 use crate::device::Device;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::mipmap::MipmapChain;
+use crate::renderer::RenderTarget;
 use crate::transform::ScreenSpace;
 
 #[derive(Debug)]
@@ -16,61 +22,307 @@ pub struct Texture {
     pub format: wgpu::TextureFormat,
 
     pub size: Size<u32, ScreenSpace>,
+    /// How the texture is viewed when bound.
+    pub view_dimension: TextureViewDimension,
+    /// The label the texture was created with.
+    pub(crate) label: String,
+    /// Used to generate the texture's mip levels, created the first time
+    /// they're generated.
+    pub(crate) mipmaps: OnceLock<Option<MipmapChain>>,
+}
+
+/// Describes a texture to create using
+/// [`Device::create_texture_with_descriptor`]. Textures may have multiple
+/// array layers, be cube maps or be three dimensional.
+#[derive(Debug, Clone)]
+pub struct TextureDescriptor<'a> {
+    pub label: Option<&'a str>,
+    pub size: Size<u32, ScreenSpace>,
+    /// The number of array layers, the number of cube faces, or the depth of
+    /// a 3D texture.
+    pub layers: u32,
+    /// How the texture is viewed when bound. [`TextureViewDimension::D3`]
+    /// creates a 3D texture, and all other dimensions create 2D textures.
+    pub view_dimension: TextureViewDimension,
+    pub format: TextureFormat,
+    pub usage: TextureUsages,
+    pub mip_level_count: u32,
+    pub sample_count: u32,
+}
+
+impl<'a> TextureDescriptor<'a> {
+    /// Describes a 2D texture with a single layer.
+    pub fn new(size: Size<u32, ScreenSpace>, format: TextureFormat, usage: TextureUsages) -> Self {
+        Self {
+            label: None,
+            size,
+            layers: 1,
+            view_dimension: TextureViewDimension::D2,
+            format,
+            usage,
+            mip_level_count: 1,
+            sample_count: 1,
+        }
+    }
+
+    /// Describes a 2D texture with `layers` array layers.
+    pub fn array(
+        size: Size<u32, ScreenSpace>,
+        layers: u32,
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Self {
+        Self {
+            layers,
+            view_dimension: TextureViewDimension::D2Array,
+            ..Self::new(size, format, usage)
+        }
+    }
+
+    /// Describes a cube map whose faces are `size` texels wide and tall. The
+    /// faces are stored as layers in the order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn cube(size: u32, format: TextureFormat, usage: TextureUsages) -> Self {
+        Self {
+            layers: 6,
+            view_dimension: TextureViewDimension::Cube,
+            ..Self::new(Size::new(size, size), format, usage)
+        }
+    }
+
+    /// Describes an array of `cubes` cube maps. The faces of each cube are
+    /// stored as 6 consecutive layers.
+    pub fn cube_array(size: u32, cubes: u32, format: TextureFormat, usage: TextureUsages) -> Self {
+        Self {
+            layers: cubes * 6,
+            view_dimension: TextureViewDimension::CubeArray,
+            ..Self::new(Size::new(size, size), format, usage)
+        }
+    }
+
+    /// Describes a 3D texture that is `depth` texels deep.
+    pub fn volume(
+        size: Size<u32, ScreenSpace>,
+        depth: u32,
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Self {
+        Self {
+            layers: depth,
+            view_dimension: TextureViewDimension::D3,
+            ..Self::new(size, format, usage)
+        }
+    }
+
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_mip_level_count(mut self, mip_level_count: u32) -> Self {
+        self.mip_level_count = mip_level_count;
+        self
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub(crate) fn dimension(&self) -> wgpu::TextureDimension {
+        match self.view_dimension {
+            TextureViewDimension::D1 => wgpu::TextureDimension::D1,
+            TextureViewDimension::D3 => wgpu::TextureDimension::D3,
+            _ => wgpu::TextureDimension::D2,
+        }
+    }
+}
+
+/// Options used when creating a texture from an encoded image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Whether the image contains sRGB encoded colors, selecting
+    /// `Rgba8UnormSrgb`, or linear data such as a normal map, selecting
+    /// `Rgba8Unorm`.
+    pub srgb: bool,
+    /// Whether the color channels are multiplied by the alpha channel before
+    /// uploading the image.
+    pub premultiply_alpha: bool,
+    /// Whether the texture has a full chain of mip levels, generated from the
+    /// image.
+    pub generate_mipmaps: bool,
+    pub usage: TextureUsages,
+    pub label: Option<String>,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            premultiply_alpha: false,
+            generate_mipmaps: false,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            label: None,
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn with_premultiplied_alpha(mut self, premultiply_alpha: bool) -> Self {
+        self.premultiply_alpha = premultiply_alpha;
+        self
+    }
+
+    pub fn with_mipmaps(mut self, generate_mipmaps: bool) -> Self {
+        self.generate_mipmaps = generate_mipmaps;
+        self
+    }
+
+    pub fn with_usage(mut self, usage: TextureUsages) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
 }
 
 impl Texture {
-    pub fn clear<T>(
-        texture: &Texture,
-        value: T,
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    ) where
-        T: Clone,
+    /// Returns the number of mip levels in a full mip chain for a texture of
+    /// `size`.
+    pub fn full_mip_level_count(size: Size<u32, ScreenSpace>) -> u32 {
+        32 - size.width.max(size.height).max(1).leading_zeros()
+    }
+
+    /// Records the commands rendering each of the texture's mip levels from
+    /// the previous, larger mip level, filtering linearly. sRGB textures are
+    /// filtered in linear space. The views and bind groups used are created
+    /// on the first call and reused afterwards.
+    ///
+    /// Returns [`Error::MipmapsUnsupported`] if the texture has multiple mip
+    /// levels but can't be both sampled and rendered to, or isn't a 2D
+    /// texture with a single layer. See
+    /// [`Device::create_texture_with_mip_levels`].
+    pub fn generate_mipmaps(&self, device: &Device, frame: &mut Frame) -> Result<(), Error> {
+        let mip_level_count = self.wgpu.mip_level_count();
+        if mip_level_count == 1 {
+            return Ok(());
+        }
+        let mipmaps = self
+            .mipmaps
+            .get_or_init(|| {
+                if self.wgpu.sample_count() == 1 && self.view_dimension == TextureViewDimension::D2
+                {
+                    MipmapChain::new(device, &self.wgpu, mip_level_count, &self.label)
+                } else {
+                    None
+                }
+            })
+            .as_ref()
+            .ok_or(Error::MipmapsUnsupported)?;
+        mipmaps.encode(&mut frame.encoder);
+        Ok(())
+    }
+
+    /// Fills the texture with `value`, which must be the size of one of the
+    /// texture format's texels, such as `f32` for an `R32Float` texture.
+    pub fn clear<T>(texture: &Texture, value: T, device: &Device) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
     {
-        let capacity = texture.size.cast::<usize>().area().get();
-        let mut texels: Vec<T> = Vec::with_capacity(capacity);
-        texels.resize(capacity, value);
+        let layout = TexelLayout::new(texture.format, texture.size)?;
+        let texels = vec![value; layout.texel_count];
 
-        let (head, body, tail) = unsafe { texels.align_to::<Rgba8>() };
-        assert!(head.is_empty());
-        assert!(tail.is_empty());
+        Self::fill(texture, &texels, device)
+    }
 
-        Self::fill(texture, body, device, encoder);
+    /// Fills the texture with `texels`, which must each be the size of one of
+    /// the texture format's texels, or blocks for compressed formats.
+    pub fn fill<T>(texture: &Texture, texels: &[T], device: &Device) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
+    {
+        Self::fill_layer(texture, texels, 0, device)
     }
 
-    pub fn fill<T: bytemuck::Pod + 'static>(
+    /// Fills the array layer, cube face or depth slice at `layer` with
+    /// `texels`.
+    pub fn fill_layer<T>(
         texture: &Texture,
         texels: &[T],
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    ) where
-        T: Clone + Copy,
+        layer: u32,
+        device: &Device,
+    ) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
     {
-        assert!(
-            texels.len() as u32 >= texture.size.area().get(),
-            "fatal: incorrect length for texel buffer"
-        );
+        Self::fill_mip_level(texture, texels, layer, 0, device)
+    }
 
-        let buf = device.create_buffer_from_slice(texels, wgpu::BufferUsages::COPY_SRC);
+    /// Fills mip level `mip_level` of the array layer, cube face or depth
+    /// slice at `layer` with `texels`. Texels of compressed formats are
+    /// blocks, such as `[u8; 8]` for BC1 or `[u8; 16]` for BC7, and cover the
+    /// mip level rounded up to whole blocks.
+    pub fn fill_mip_level<T>(
+        texture: &Texture,
+        texels: &[T],
+        layer: u32,
+        mip_level: u32,
+        device: &Device,
+    ) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
+    {
+        if mip_level >= texture.wgpu.mip_level_count() {
+            return Err(Error::InvalidTransferRegion);
+        }
+        let mip_size = texture
+            .extent
+            .mip_level_size(mip_level, texture.wgpu.dimension());
 
-        Self::copy(
-            &texture.wgpu,
-            SizedRect::new(Point::default(), texture.size),
-            texels.len() as u32 / texture.extent.height * 4,
-            texture.extent,
-            &buf,
-            encoder,
-        );
+        Self::upload(
+            texture,
+            texels,
+            SizedRect::new(Point::default(), Size::new(mip_size.width, mip_size.height)),
+            layer,
+            mip_level,
+            device,
+        )
     }
 
-    pub fn transfer<T: bytemuck::Pod + 'static>(
+    /// Transfers `texels` to `rect` of the texture, whose Y axis points
+    /// upwards. The texels must each be the size of one of the texture
+    /// format's texels.
+    pub fn transfer<T>(
         texture: &Texture,
         texels: &[T],
         rect: SizedRect<i32, ScreenSpace>,
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    ) where
-        T: Into<Rgba8> + Clone + Copy,
+        device: &Device,
+    ) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
+    {
+        Self::transfer_layer(texture, texels, rect, 0, device)
+    }
+
+    /// Transfers `texels` to `rect` of the array layer, cube face or depth
+    /// slice at `layer`. The faces of cube maps are the layers 0 to 5, in the
+    /// order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn transfer_layer<T>(
+        texture: &Texture,
+        texels: &[T],
+        rect: SizedRect<i32, ScreenSpace>,
+        layer: u32,
+        device: &Device,
+    ) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
     {
         // Wgpu's coordinate system has a downwards pointing Y axis.
         let destination = rect.as_extents();
@@ -85,6 +337,13 @@ impl Texture {
                 destination.extent.y.max(destination.origin.y),
             ),
         );
+        if destination.origin.x < 0
+            || destination.origin.y < 0
+            || destination.extent.x as u32 > texture.size.width
+            || destination.extent.y as u32 > texture.size.height
+        {
+            return Err(Error::InvalidTransferRegion);
+        }
         // flip y, making it negative in the y direction
         let destination = ExtentsRect::new(
             Point::new(destination.origin.x, destination.extent.y),
@@ -102,26 +361,50 @@ impl Texture {
             texture.size.height - rect.origin.y as u32,
         );
 
+        Self::upload(
+            texture,
+            texels,
+            SizedRect::new(destination_point, destination_size),
+            layer,
+            0,
+            device,
+        )
+    }
+
+    /// Creates a 2D view of the array layer or cube face at `layer`, which
+    /// can be rendered to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the texture is a 3D texture or `layer` is out of range.
+    pub fn layer_view(&self, layer: u32) -> wgpu::TextureView {
         assert!(
-            destination_size.area() <= texture.size.area(),
-            "fatal: transfer size must be <= texture size"
+            self.view_dimension != TextureViewDimension::D3,
+            "fatal: layers of 3D textures can't be viewed individually"
+        );
+        assert!(
+            layer < self.extent.depth_or_array_layers,
+            "fatal: layer out of range"
         );
 
-        let buf = device.create_buffer_from_slice(texels, wgpu::BufferUsages::COPY_SRC);
+        self.wgpu.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("easygpu texture layer"),
+            dimension: Some(TextureViewDimension::D2),
+            mip_level_count: Some(1),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    }
 
-        let extent = wgpu::Extent3d {
-            width: destination_size.width,
-            height: destination_size.height,
-            depth_or_array_layers: 1,
-        };
-        Self::copy(
-            &texture.wgpu,
-            SizedRect::new(destination_point, destination_size),
-            texels.len() as u32 / destination_size.height * 4,
-            extent,
-            &buf,
-            encoder,
-        );
+    /// Returns a render target drawing into the array layer or cube face at
+    /// `layer`, using `depth` as the depth buffer. See
+    /// [`Texture::layer_view`].
+    pub fn layer_target<'a>(&self, layer: u32, depth: &'a DepthBuffer) -> LayerTarget<'a> {
+        LayerTarget {
+            view: self.layer_view(layer),
+            depth,
+        }
     }
 
     fn blit(
@@ -164,40 +447,208 @@ impl Texture {
         );
     }
 
-    fn copy(
-        texture: &wgpu::Texture,
+    /// Writes `texels` to `destination` of mip level `mip_level` of the
+    /// texture's layer at `layer`, checking that they match the texture's
+    /// format.
+    fn upload<T>(
+        texture: &Texture,
+        texels: &[T],
         destination: SizedRect<u32, ScreenSpace>,
-        bytes_per_row: u32,
-        extent: wgpu::Extent3d,
-        buffer: &wgpu::Buffer,
-        encoder: &mut wgpu::CommandEncoder,
-    ) {
-        encoder.copy_buffer_to_texture(
-            wgpu::ImageCopyBuffer {
-                buffer,
-                layout: wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(bytes_per_row),
-                    rows_per_image: Some(destination.size.height),
-                },
-            },
+        layer: u32,
+        mip_level: u32,
+        device: &Device,
+    ) -> Result<(), Error>
+    where
+        T: bytemuck::Pod,
+    {
+        let layout = TexelLayout::new(texture.format, destination.size)?;
+        layout.check_texel_size::<T>(texture.format)?;
+        layout.check_texel_count(texels.len())?;
+
+        Self::write_bytes(
+            texture,
+            bytemuck::cast_slice(&texels[..layout.texel_count]),
+            destination,
+            layer,
+            mip_level,
+            device,
+        )
+    }
+
+    /// Writes the tightly packed texels in `bytes` to `destination` of mip
+    /// level `mip_level` of the texture's layer at `layer`.
+    ///
+    /// The texels are staged by the queue instead of a temporary buffer, so
+    /// the write takes effect before the commands of the next submission,
+    /// rather than in the order it was recorded in.
+    pub(crate) fn write_bytes(
+        texture: &Texture,
+        bytes: &[u8],
+        destination: SizedRect<u32, ScreenSpace>,
+        layer: u32,
+        mip_level: u32,
+        device: &Device,
+    ) -> Result<(), Error> {
+        let layout = TexelLayout::new(texture.format, destination.size)?;
+        if layout.texel_count == 0 {
+            return Ok(());
+        }
+        layout.check_texel_count(bytes.len() / layout.block_size as usize)?;
+        if mip_level >= texture.wgpu.mip_level_count() {
+            return Err(Error::InvalidTransferRegion);
+        }
+
+        let mip_size = texture
+            .extent
+            .mip_level_size(mip_level, texture.wgpu.dimension());
+        if layer >= mip_size.depth_or_array_layers {
+            return Err(Error::InvalidTransferRegion);
+        }
+        let (x, y) = (destination.origin.x, destination.origin.y);
+        let copy_size = TexelLayout::copy_size(texture.format, mip_size, destination)
+            .ok_or(Error::InvalidTransferRegion)?;
+
+        device.queue.write_texture(
             wgpu::ImageCopyTexture {
-                texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d {
-                    x: destination.origin.x,
-                    y: destination.origin.y,
-                    z: 0,
-                },
-                aspect: TextureAspect::All,
+                texture: &texture.wgpu,
+                mip_level,
+                origin: wgpu::Origin3d { x, y, z: layer },
+                aspect: copy_aspect(texture.format),
+            },
+            &bytes[..layout.byte_len()],
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(layout.bytes_per_row),
+                rows_per_image: Some(layout.rows),
+            },
+            wgpu::Extent3d {
+                width: copy_size.width,
+                height: copy_size.height,
+                depth_or_array_layers: 1,
             },
-            extent,
         );
+
+        Ok(())
+    }
+}
+
+/// Returns the aspect of textures of `format` that is copied to and from
+/// buffers. Only one aspect of depth-stencil textures can be copied at once.
+pub(crate) fn copy_aspect(format: TextureFormat) -> TextureAspect {
+    if format.has_depth_aspect() {
+        TextureAspect::DepthOnly
+    } else if format.has_stencil_aspect() {
+        TextureAspect::StencilOnly
+    } else {
+        TextureAspect::All
+    }
+}
+
+/// The layout of tightly packed texels uploaded to a region of a texture.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TexelLayout {
+    /// The size of each texel, or block for compressed formats, in bytes.
+    pub block_size: u32,
+    pub bytes_per_row: u32,
+    /// The number of rows of blocks in the region.
+    pub rows: u32,
+    /// The number of texels, or blocks for compressed formats, in the region.
+    pub texel_count: usize,
+}
+
+impl TexelLayout {
+    /// Returns the layout of a `size` region of a texture of `format`, or
+    /// [`Error::UnsupportedUploadFormat`] if texels can't be copied to
+    /// textures of `format`.
+    pub fn new(format: TextureFormat, size: Size<u32, ScreenSpace>) -> Result<Self, Error> {
+        // wgpu only allows copies to the depth aspect of `Depth16Unorm`.
+        if format.has_depth_aspect() && format != TextureFormat::Depth16Unorm {
+            return Err(Error::UnsupportedUploadFormat(format));
+        }
+        let block_size = format
+            .block_size(Some(copy_aspect(format)))
+            .ok_or(Error::UnsupportedUploadFormat(format))?;
+        let (block_width, block_height) = format.block_dimensions();
+        let columns = size.width.div_ceil(block_width);
+        let rows = size.height.div_ceil(block_height);
+
+        Ok(Self {
+            block_size,
+            bytes_per_row: columns * block_size,
+            rows,
+            texel_count: columns as usize * rows as usize,
+        })
+    }
+
+    /// Returns the size of the copy of `rect` of a mip level of `format`
+    /// whose size is `mip_size`, or `None` if `rect` is outside of the mip
+    /// level or isn't aligned to the format's blocks.
+    ///
+    /// Copies of compressed textures are made in whole blocks, which may
+    /// extend past the logical size of the mip level, so regions ending at
+    /// its edge are extended to the end of the last block.
+    pub fn copy_size(
+        format: TextureFormat,
+        mip_size: wgpu::Extent3d,
+        rect: SizedRect<u32, ScreenSpace>,
+    ) -> Option<Size<u32, ScreenSpace>> {
+        let (block_width, block_height) = format.block_dimensions();
+        let physical_size = mip_size.physical_size(format);
+        let (x, y) = (rect.origin.x, rect.origin.y);
+        let (mut width, mut height) = (rect.size.width, rect.size.height);
+        if x + width > mip_size.width
+            || y + height > mip_size.height
+            || x % block_width != 0
+            || y % block_height != 0
+        {
+            return None;
+        }
+        if x + width == mip_size.width {
+            width = physical_size.width - x;
+        }
+        if y + height == mip_size.height {
+            height = physical_size.height - y;
+        }
+        if width % block_width != 0 || height % block_height != 0 {
+            return None;
+        }
+        Some(Size::new(width, height))
+    }
+
+    /// Returns an error if `T` isn't the size of the texels of `format`.
+    pub fn check_texel_size<T>(&self, format: TextureFormat) -> Result<(), Error> {
+        let texel_size = std::mem::size_of::<T>();
+        if texel_size == self.block_size as usize {
+            Ok(())
+        } else {
+            Err(Error::TexelSizeMismatch {
+                format,
+                expected: self.block_size,
+                found: texel_size,
+            })
+        }
+    }
+
+    /// Returns an error if `found` texels don't cover the region.
+    pub fn check_texel_count(&self, found: usize) -> Result<(), Error> {
+        if found < self.texel_count {
+            Err(Error::NotEnoughTexels {
+                expected: self.texel_count,
+                found,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The number of bytes in the region.
+    pub fn byte_len(&self) -> usize {
+        self.texel_count * self.block_size as usize
     }
 }
 
 impl Bind for Texture {
-    fn binding(&self, index: u32) -> wgpu::BindGroupEntry {
+    fn binding(&self, index: u32) -> wgpu::BindGroupEntry<'_> {
         wgpu::BindGroupEntry {
             binding: index,
             resource: wgpu::BindingResource::TextureView(&self.view),
@@ -208,22 +659,21 @@ impl Bind for Texture {
 impl Canvas for Texture {
     type Color = Rgba8;
 
-    fn fill(&self, buf: &[Rgba8], device: &mut Device, encoder: &mut wgpu::CommandEncoder) {
-        Texture::fill(self, buf, device, encoder);
+    fn fill(&self, buf: &[Rgba8], device: &Device) -> Result<(), Error> {
+        Texture::fill(self, buf, device)
     }
 
-    fn clear(&self, color: Rgba8, device: &mut Device, encoder: &mut wgpu::CommandEncoder) {
-        Texture::clear(self, color, device, encoder);
+    fn clear(&self, color: Rgba8, device: &Device) -> Result<(), Error> {
+        Texture::clear(self, color, device)
     }
 
     fn transfer(
         &self,
         buf: &[Rgba8],
         rect: SizedRect<i32, ScreenSpace>,
-        device: &mut Device,
-        encoder: &mut wgpu::CommandEncoder,
-    ) {
-        Texture::transfer(self, buf, rect, device, encoder);
+        device: &Device,
+    ) -> Result<(), Error> {
+        Texture::transfer(self, buf, rect, device)
     }
 
     fn blit(
@@ -236,8 +686,155 @@ impl Canvas for Texture {
     }
 }
 
+/// A single layer or cube face of a texture, rendered to with a depth buffer.
+/// Created by [`Texture::layer_target`].
+#[derive(Debug)]
+pub struct LayerTarget<'a> {
+    pub view: wgpu::TextureView,
+    pub depth: &'a DepthBuffer,
+}
+
+impl RenderTarget for LayerTarget<'_> {
+    fn color_target(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    fn zdepth_target(&self) -> &wgpu::TextureView {
+        &self.depth.texture.view
+    }
+}
+
 impl From<Framebuffer> for Texture {
     fn from(fb: Framebuffer) -> Self {
         fb.texture
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use figures::{Point, Size, SizedRect};
+    use wgpu::TextureFormat;
+
+    use super::TexelLayout;
+    use crate::color::{Bgra8, Rgba8};
+    use crate::error::Error;
+
+    fn layout(format: TextureFormat, width: u32, height: u32) -> TexelLayout {
+        TexelLayout::new(format, Size::new(width, height)).unwrap()
+    }
+
+    #[test]
+    fn rows_are_sized_by_the_format() {
+        let r8 = layout(TextureFormat::R8Unorm, 5, 3);
+        assert_eq!((r8.block_size, r8.bytes_per_row, r8.rows), (1, 5, 3));
+        assert_eq!(r8.texel_count, 15);
+        assert_eq!(r8.byte_len(), 15);
+
+        let rgba8 = layout(TextureFormat::Rgba8UnormSrgb, 5, 3);
+        assert_eq!(
+            (rgba8.block_size, rgba8.bytes_per_row, rgba8.rows),
+            (4, 20, 3)
+        );
+        assert_eq!(rgba8.texel_count, 15);
+
+        let rgba32 = layout(TextureFormat::Rgba32Float, 5, 3);
+        assert_eq!(
+            (rgba32.block_size, rgba32.bytes_per_row, rgba32.rows),
+            (16, 80, 3)
+        );
+        assert_eq!(rgba32.byte_len(), 240);
+    }
+
+    #[test]
+    fn compressed_rows_are_sized_in_blocks() {
+        // A 10x6 BC1 region is 3 blocks wide and 2 blocks tall.
+        let bc1 = layout(TextureFormat::Bc1RgbaUnorm, 10, 6);
+        assert_eq!((bc1.block_size, bc1.bytes_per_row, bc1.rows), (8, 24, 2));
+        assert_eq!(bc1.texel_count, 6);
+
+        let bc7 = layout(TextureFormat::Bc7RgbaUnorm, 4, 4);
+        assert_eq!((bc7.block_size, bc7.bytes_per_row, bc7.rows), (16, 16, 1));
+    }
+
+    #[test]
+    fn texels_must_match_the_format_size() {
+        let rgba8 = layout(TextureFormat::Rgba8Unorm, 2, 2);
+        assert!(rgba8
+            .check_texel_size::<Rgba8>(TextureFormat::Rgba8Unorm)
+            .is_ok());
+        assert!(rgba8
+            .check_texel_size::<Bgra8>(TextureFormat::Rgba8Unorm)
+            .is_ok());
+        assert!(matches!(
+            rgba8.check_texel_size::<u8>(TextureFormat::Rgba8Unorm),
+            Err(Error::TexelSizeMismatch {
+                format: TextureFormat::Rgba8Unorm,
+                expected: 4,
+                found: 1,
+            })
+        ));
+        assert!(matches!(
+            layout(TextureFormat::R8Unorm, 2, 2).check_texel_size::<u32>(TextureFormat::R8Unorm),
+            Err(Error::TexelSizeMismatch {
+                expected: 1,
+                found: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn texels_must_cover_the_region() {
+        let rgba8 = layout(TextureFormat::Rgba8Unorm, 3, 2);
+        assert!(rgba8.check_texel_count(6).is_ok());
+        assert!(rgba8.check_texel_count(7).is_ok());
+        assert!(matches!(
+            rgba8.check_texel_count(5),
+            Err(Error::NotEnoughTexels {
+                expected: 6,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn depth_formats_that_cant_be_copied_to_are_rejected() {
+        for format in [
+            TextureFormat::Depth32Float,
+            TextureFormat::Depth24Plus,
+            TextureFormat::Depth24PlusStencil8,
+            TextureFormat::Depth32FloatStencil8,
+        ] {
+            assert!(matches!(
+                TexelLayout::new(format, Size::new(4, 4)),
+                Err(Error::UnsupportedUploadFormat(unsupported)) if unsupported == format
+            ));
+        }
+        let layout = TexelLayout::new(TextureFormat::Depth16Unorm, Size::new(4, 4)).unwrap();
+        assert_eq!(layout.bytes_per_row, 8);
+    }
+
+    #[test]
+    fn compressed_copies_are_extended_to_whole_blocks_at_the_edge() {
+        let mip_size = wgpu::Extent3d {
+            width: 10,
+            height: 6,
+            depth_or_array_layers: 1,
+        };
+        let copy_size = |x, y, width, height| {
+            TexelLayout::copy_size(
+                TextureFormat::Bc1RgbaUnorm,
+                mip_size,
+                SizedRect::new(Point::new(x, y), Size::new(width, height)),
+            )
+        };
+
+        assert_eq!(copy_size(0, 0, 10, 6), Some(Size::new(12, 8)));
+        assert_eq!(copy_size(4, 4, 4, 2), Some(Size::new(4, 4)));
+        // Unaligned origins, sizes not ending at the edge, and regions outside
+        // of the mip level.
+        assert_eq!(copy_size(2, 0, 8, 6), None);
+        assert_eq!(copy_size(0, 0, 6, 6), None);
+        assert_eq!(copy_size(8, 0, 4, 6), None);
+    }
+}
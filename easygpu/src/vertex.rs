@@ -50,7 +50,7 @@ impl VertexLayout {
         vl
     }
 
-    pub fn to_wgpu(&self) -> wgpu::VertexBufferLayout {
+    pub fn to_wgpu(&self) -> wgpu::VertexBufferLayout<'_> {
         wgpu::VertexBufferLayout {
             array_stride: self.size as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
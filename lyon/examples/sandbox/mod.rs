@@ -1,6 +1,6 @@
 use easygpu::figures::Size;
 use easygpu::prelude::*;
-use easygpu::wgpu::{PresentMode, TextureUsages};
+use easygpu::wgpu::PresentMode;
 use easygpu_lyon::{LyonPipeline, Srgb, VertexShaderSource};
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
@@ -25,19 +25,14 @@ pub trait Sandbox: Sized + 'static {
         let mut renderer = futures::executor::block_on(Renderer::for_surface(
             surface,
             &instance,
+            &AdapterPolicy::default(),
             MSAA_SAMPLE_COUNT,
+            &DeviceOptions::default(),
         ))?;
         let sandbox = Self::create(&renderer);
         let size = Size::new(size.width, size.height).cast::<u32>();
 
-        renderer.configure(size, PresentMode::Fifo, Srgb::sampler_format());
-
-        let mut multisample_texture = renderer.texture(
-            size,
-            Srgb::sampler_format(),
-            TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
-            MSAA_SAMPLE_COUNT > 1,
-        );
+        renderer.configure(size, PresentMode::Fifo, Srgb::sampler_format())?;
 
         event_loop.run(move |event, _, control_flow| match event {
             Event::WindowEvent { event, .. } => match event {
@@ -46,14 +41,12 @@ pub trait Sandbox: Sized + 'static {
                 }
                 WindowEvent::Resized(new_size) => {
                     let new_size = Size::new(new_size.width, new_size.height).cast::<u32>();
-                    renderer.configure(new_size, PresentMode::Fifo, Srgb::sampler_format());
-                    // Recreate the texture to match the new output size.
-                    multisample_texture = renderer.texture(
-                        new_size,
-                        Srgb::sampler_format(),
-                        TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
-                        MSAA_SAMPLE_COUNT > 1,
-                    );
+                    if let Err(err) =
+                        renderer.configure(new_size, PresentMode::Fifo, Srgb::sampler_format())
+                    {
+                        eprintln!("unable to configure surface: {}", err);
+                        *control_flow = ControlFlow::Exit;
+                    }
                 }
                 WindowEvent::KeyboardInput {
                     input:
@@ -68,8 +61,11 @@ pub trait Sandbox: Sized + 'static {
                 }
                 _ => {}
             },
-            Event::RedrawRequested(_) => {
-                if let Ok(output) = renderer.current_frame() {
+            Event::RedrawRequested(_) => match renderer
+                .surface()
+                .and_then(|surface| renderer.current_frame(surface))
+            {
+                Ok(Some(output)) => {
                     let mut frame = renderer.frame();
                     renderer.update_pipeline(
                         sandbox.pipeline(),
@@ -84,17 +80,19 @@ pub trait Sandbox: Sized + 'static {
                     );
 
                     {
-                        let mut pass = frame.pass(
-                            PassOp::Clear(Rgba::TRANSPARENT),
-                            &output,
-                            Some(&multisample_texture.view),
-                        );
+                        let mut pass =
+                            frame.resolving_pass(PassOp::Clear(Rgba::TRANSPARENT), &output);
 
                         sandbox.render(&mut pass);
                     }
                     renderer.present(frame);
                 }
-            }
+                Ok(None) => {}
+                Err(err) => {
+                    eprintln!("unable to render frame: {}", err);
+                    *control_flow = ControlFlow::Exit;
+                }
+            },
             _ => {
                 *control_flow = ControlFlow::Wait;
             }
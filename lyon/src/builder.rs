@@ -44,8 +44,12 @@ impl ShapeBuilder {
     /// This does not consume the builder, because wgpu copies the buffer rather
     /// than taking ownerhip.
     pub fn prepare(&self, renderer: &Renderer) -> Shape {
-        let verticies = renderer.device.create_buffer(&self.vertices);
-        let indicies = renderer.device.create_index(&self.indicies);
+        let verticies = renderer
+            .device
+            .create_buffer(&self.vertices, Some("easygpu-lyon shape vertices"));
+        let indicies = renderer
+            .device
+            .create_index(&self.indicies, Some("easygpu-lyon shape indices"));
 
         Shape {
             index_count: self.indicies.len() as u32,
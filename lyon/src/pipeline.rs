@@ -59,10 +59,13 @@ where
 
     fn description() -> PipelineDescription<'a> {
         PipelineDescription {
+            label: Some("easygpu-lyon shape pipeline"),
             vertex_layout: &[VertexFormat::Float3, VertexFormat::UByte4],
             pipeline_layout: &[Set(&[Binding {
                 binding: BindingType::UniformBuffer,
                 stage: ShaderStages::VERTEX,
+                count: None,
+                min_binding_size: None,
             }])],
             vertex_shader: T::shader(),
             fragment_shader: include_bytes!("shaders/shape.frag.spv"),
@@ -72,8 +75,15 @@ where
     fn setup(pipeline: Pipeline, dev: &Device) -> Self {
         let transform = ScreenTransformation::identity().to_array();
         let ortho = ScreenTransformation::identity().to_array();
-        let uniforms = dev.create_uniform_buffer(&[self::Uniforms { ortho, transform }]);
-        let bindings = dev.create_binding_group(&pipeline.layout.sets[0], &[&uniforms]);
+        let uniforms = dev.create_uniform_buffer(
+            &[self::Uniforms { ortho, transform }],
+            Some("easygpu-lyon shape uniforms"),
+        );
+        let bindings = dev.create_binding_group(
+            &pipeline.layout.sets[0],
+            &[&uniforms],
+            Some("easygpu-lyon shape bindings"),
+        );
 
         Self {
             pipeline: PipelineCore {
@@ -81,7 +91,7 @@ where
                 uniforms,
                 bindings,
             },
-            _phantom: PhantomData::default(),
+            _phantom: PhantomData,
         }
     }
 
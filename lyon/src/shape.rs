@@ -4,9 +4,10 @@ use bytemuck::{Pod, Zeroable};
 use easygpu::buffers::{IndexBuffer, VertexBuffer};
 use easygpu::color::Rgba8;
 
+/// A vertex of a tessellated shape.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
-pub(crate) struct Vertex {
+pub struct Vertex {
     pub position: [f32; 3],
     pub color: Rgba8,
 }
@@ -16,9 +17,9 @@ pub struct Shape {
     /// Number of indices contained in `indices`
     pub index_count: u32,
     /// The vertices stored in a vertex buffer
-    pub vertices: Arc<VertexBuffer>,
+    pub vertices: Arc<VertexBuffer<Vertex>>,
     /// An index buffer representing a TriangleList of indices within `vertices`
-    pub indices: Arc<IndexBuffer>,
+    pub indices: Arc<IndexBuffer<u16>>,
 }
 
 impl Shape {
@@ -31,7 +32,7 @@ impl Shape {
     /// * `pass`- The render pass to draw to.
     pub fn draw<'a>(&'a self, pass: &mut easygpu::wgpu::RenderPass<'a>) {
         pass.set_vertex_buffer(0, self.vertices.slice());
-        pass.set_index_buffer(self.indices.slice(), easygpu::wgpu::IndexFormat::Uint16);
+        pass.set_index_buffer(self.indices.slice(), self.indices.format());
         pass.draw_indexed(0..self.index_count, 0, 0..1)
     }
 }